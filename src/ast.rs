@@ -3,6 +3,35 @@
 //! This module contains all the data structures that represent the parsed
 //! structure of PaneerLang programs, including types, statements, and expressions.
 
+use std::cell::OnceCell;
+
+/// Byte range of a node in the source code
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Span {
+    /// Offset of the first byte
+    pub start: usize,
+    /// Offset one past the last byte
+    pub end: usize,
+}
+
+impl Span {
+    /// Creates a span covering `start..end`
+    pub fn new(start: usize, end: usize) -> Self {
+        Span { start, end }
+    }
+
+    /// Returns a span covering both `self` and `other`
+    pub fn to(self, other: Span) -> Span {
+        Span::new(self.start.min(other.start), self.end.max(other.end))
+    }
+}
+
+impl From<std::ops::Range<usize>> for Span {
+    fn from(range: std::ops::Range<usize>) -> Self {
+        Span::new(range.start, range.end)
+    }
+}
+
 /// Represents the type system of PaneerLang
 /// Supports primitive types and generic arrays
 #[derive(Debug, Clone, PartialEq)]
@@ -17,6 +46,19 @@ pub enum Type {
     Bool,
     /// Homogeneous array of a specific type
     Array(Box<Type>),
+    /// Map from keys of the first type to values of the second, which
+    /// iterates in insertion order
+    Map(Box<Type>, Box<Type>),
+    /// Fixed-size group of values of the given types, such as `(int, string)`
+    Tuple(Vec<Type>),
+    /// A value of the inner type or `khaali`, written `int?`
+    Optional(Box<Type>),
+    /// No value, returned by functions declared `kuch_nahi` or without a
+    /// return type
+    Unit,
+    /// Struct declared with `dhancha` or enum declared with `prakar`,
+    /// referred to by name
+    Named(String),
 }
 
 /// Root node of the AST representing a complete PaneerLang program
@@ -27,6 +69,8 @@ pub struct Program {
 }
 
 /// Represents all possible statement types in PaneerLang
+///
+/// Every statement records the span of source text it was parsed from.
 #[derive(Debug, Clone)]
 pub enum Statement {
     /// Variable declaration: `ye name: type = value;`
@@ -34,6 +78,19 @@ pub enum Statement {
         name: String,
         type_annotation: Type,
         initializer: Expression,
+        /// Text of the `///` comments written just above the declaration
+        doc: Option<String>,
+        span: Span,
+    },
+    /// Tuple destructuring: `ye (a, b): (int, string) = value;`
+    ///
+    /// Each name is declared with the matching element type; `_` skips an
+    /// element.
+    TupleDecl {
+        names: Vec<String>,
+        type_annotation: Type,
+        initializer: Expression,
+        span: Span,
     },
     /// Function declaration: `func name(params) return_type { body }`
     FuncDecl {
@@ -41,63 +98,342 @@ pub enum Statement {
         params: Vec<(String, Type)>,
         return_type: Type,
         body: Vec<Statement>,
+        /// Text of the `///` comments written just above the declaration
+        doc: Option<String>,
+        span: Span,
+    },
+    /// Struct declaration: `dhancha Name { field: type, ... }`
+    StructDecl {
+        name: String,
+        fields: Vec<(String, Type)>,
+        /// Text of the `///` comments written just above the declaration
+        doc: Option<String>,
+        span: Span,
+    },
+    /// Enum declaration: `prakar Name { Variant, Variant(type, ...), ... }`
+    ///
+    /// Each variant lists the types of its payload, which is empty for
+    /// variants that carry no data.
+    EnumDecl {
+        name: String,
+        variants: Vec<(String, Vec<Type>)>,
+        /// Text of the `///` comments written just above the declaration
+        doc: Option<String>,
+        span: Span,
     },
     /// Expression statement: any expression followed by semicolon
-    ExprStmt { expression: Expression },
+    ExprStmt { expression: Expression, span: Span },
+    /// Assignment: `name = value;` or `array[index] = value;`
+    ///
+    /// Compound forms such as `name += value;` carry the operator to apply
+    /// between the current value and `value` before storing the result.
+    Assignment {
+        target: Expression,
+        operator: Option<BinaryOperator>,
+        value: Expression,
+        span: Span,
+    },
     /// If statement: `agar condition { then_branch } varna { else_branch }`
+    ///
+    /// An else-if chain (`varna agar cond { ... }`) is stored as an else branch
+    /// containing a single nested `IfStmt`.
     IfStmt {
         condition: Expression,
         then_branch: Vec<Statement>,
         else_branch: Option<Vec<Statement>>,
+        span: Span,
+    },
+    /// Pattern test: `agar ye pattern = value { then_branch } varna { else_branch }`
+    ///
+    /// The then branch runs with the pattern's names bound when `value`
+    /// matches, and the else branch runs otherwise.
+    IfLetStmt {
+        pattern: Pattern,
+        value: Expression,
+        then_branch: Vec<Statement>,
+        else_branch: Option<Vec<Statement>>,
+        span: Span,
     },
     /// Return statement: `return value;` or `wapas kar value;`
-    ReturnStmt { value: Option<Expression> },
+    ReturnStmt {
+        value: Option<Expression>,
+        span: Span,
+    },
+    /// Break statement: `ruko;`
+    BreakStmt { span: Span },
+    /// Continue statement: `aage badho;`
+    ContinueStmt { span: Span },
     /// While loop: `jabtak condition { body }`
     WhileStmt {
         condition: Expression,
         body: Vec<Statement>,
+        span: Span,
     },
     /// For loop: `har variable mein iterable { body }`
+    ///
+    /// With a second name, `har key, value mein iterable`, `variable` holds
+    /// each map key or array index and `value_variable` the matching value.
+    /// With one name a map yields its keys. `variable_type` and `value_type`
+    /// are filled in by the type checker with the static types of the two
+    /// names, which the interpreter declares them with.
     ForStmt {
         variable: String,
+        value_variable: Option<String>,
+        variable_type: OnceCell<Type>,
+        value_type: OnceCell<Type>,
         iterable: Expression,
         body: Vec<Statement>,
+        span: Span,
     },
+    /// Range loop: `har variable se start tak end { body }`
+    ///
+    /// `tak` is inclusive, so `har i se 1 tak 3` visits 1, 2 and 3. An optional
+    /// `kadam step` sets the increment, which may be negative to count down.
+    RangeForStmt {
+        variable: String,
+        start: Expression,
+        end: Expression,
+        step: Option<Expression>,
+        body: Vec<Statement>,
+        span: Span,
+    },
+    /// Match statement: `jab value { pattern => body ... }`
+    ///
+    /// Only the first arm whose pattern matches and whose guard holds runs.
+    /// When no arm matches, nothing happens.
+    MatchStmt {
+        subject: Expression,
+        arms: Vec<MatchArm>,
+        span: Span,
+    },
+}
+
+impl Statement {
+    /// Returns the source span this statement was parsed from
+    pub fn span(&self) -> Span {
+        match self {
+            Statement::VarDecl { span, .. }
+            | Statement::TupleDecl { span, .. }
+            | Statement::FuncDecl { span, .. }
+            | Statement::StructDecl { span, .. }
+            | Statement::EnumDecl { span, .. }
+            | Statement::ExprStmt { span, .. }
+            | Statement::Assignment { span, .. }
+            | Statement::IfStmt { span, .. }
+            | Statement::IfLetStmt { span, .. }
+            | Statement::ReturnStmt { span, .. }
+            | Statement::BreakStmt { span }
+            | Statement::ContinueStmt { span }
+            | Statement::WhileStmt { span, .. }
+            | Statement::ForStmt { span, .. }
+            | Statement::RangeForStmt { span, .. }
+            | Statement::MatchStmt { span, .. } => *span,
+        }
+    }
 }
 
+/// One arm of a `jab` statement: `pattern agar guard => body`
+#[derive(Debug, Clone)]
+pub struct MatchArm {
+    pub pattern: Pattern,
+    /// Condition checked after the pattern matches, with its names bound
+    pub guard: Option<Expression>,
+    pub body: Vec<Statement>,
+    pub span: Span,
+}
+
+/// A pattern tested against a value by a `jab` arm or `agar ye`
+#[derive(Debug, Clone)]
+pub enum Pattern {
+    /// `_`, matching any value
+    Wildcard { span: Span },
+    /// A literal such as `1`, `-2.5`, `"chai"` or `true`, matching an equal value
+    Literal { value: LiteralValue, span: Span },
+    /// `start tak end`, matching numbers in the inclusive range
+    Range {
+        start: LiteralValue,
+        end: LiteralValue,
+        span: Span,
+    },
+    /// A name, matching any value and binding it to that name
+    ///
+    /// `binding_type` is filled in by the type checker with the static type
+    /// of the name, which the interpreter declares the binding with.
+    Binding {
+        name: String,
+        binding_type: OnceCell<Type>,
+        span: Span,
+    },
+    /// `[first, second, ..rest]`, matching arrays element by element
+    ///
+    /// Without a rest pattern the array length must match exactly. `rest` is
+    /// `Some(None)` for a bare `..` and `Some(Some(name))` for `..name`, which
+    /// binds the remaining elements as an array, declared with the static
+    /// type the type checker stores in `rest_type`.
+    Array {
+        elements: Vec<Pattern>,
+        rest: Option<Option<String>>,
+        rest_type: OnceCell<Type>,
+        span: Span,
+    },
+    /// `Enum::Variant(a, b)`, matching that variant and its payload
+    ///
+    /// `payload` is empty for variants without data.
+    Variant {
+        enum_name: String,
+        variant: String,
+        payload: Vec<Pattern>,
+        span: Span,
+    },
+}
+
+impl Pattern {
+    /// Returns the source span this pattern was parsed from
+    pub fn span(&self) -> Span {
+        match self {
+            Pattern::Wildcard { span }
+            | Pattern::Literal { span, .. }
+            | Pattern::Range { span, .. }
+            | Pattern::Binding { span, .. }
+            | Pattern::Array { span, .. }
+            | Pattern::Variant { span, .. } => *span,
+        }
+    }
+
+    /// Whether the pattern matches every value of the type it is checked against
+    pub fn is_irrefutable(&self) -> bool {
+        match self {
+            Pattern::Wildcard { .. } | Pattern::Binding { .. } => true,
+            Pattern::Array { elements, rest, .. } => elements.is_empty() && rest.is_some(),
+            Pattern::Literal { .. } | Pattern::Range { .. } | Pattern::Variant { .. } => false,
+        }
+    }
+}
+
+/// Represents all possible expression types in PaneerLang
+///
+/// Every expression records the span of source text it was parsed from.
 #[derive(Debug, Clone)]
 pub enum Expression {
     Binary {
         left: Box<Expression>,
         operator: BinaryOperator,
         right: Box<Expression>,
+        span: Span,
     },
     Unary {
         operator: UnaryOperator,
         operand: Box<Expression>,
+        span: Span,
     },
     Call {
         callee: Box<Expression>,
         arguments: Vec<Expression>,
+        span: Span,
     },
     Variable {
         name: String,
+        span: Span,
     },
     Literal {
         value: LiteralValue,
+        span: Span,
     },
     MethodCall {
         object: Box<Expression>,
         method: String,
         arguments: Vec<Expression>,
+        span: Span,
     },
     ArrayLiteral {
         elements: Vec<Expression>,
+        span: Span,
+    },
+    /// Tuple literal: `(7, "chai")`
+    TupleLiteral {
+        elements: Vec<Expression>,
+        span: Span,
+    },
+    /// Tuple element access: `pair.0`
+    TupleAccess {
+        tuple: Box<Expression>,
+        index: usize,
+        span: Span,
     },
     ArrayAccess {
         array: Box<Expression>,
         index: Box<Expression>,
+        span: Span,
     },
+    /// Map literal: `{"chai": 20, "samosa": 15}`
+    MapLiteral {
+        entries: Vec<(Expression, Expression)>,
+        span: Span,
+    },
+    /// Struct literal: `Point { x: 1, y: 2 }`
+    StructLiteral {
+        name: String,
+        fields: Vec<(String, Expression)>,
+        span: Span,
+    },
+    /// Enum value: `Status::Paid(10.5)` or `Status::Pending`
+    EnumVariant {
+        enum_name: String,
+        variant: String,
+        arguments: Vec<Expression>,
+        span: Span,
+    },
+    /// Field access: `point.x`
+    FieldAccess {
+        object: Box<Expression>,
+        field: String,
+        span: Span,
+    },
+    /// Conditional expression: `agar condition toh a varna b`
+    Conditional {
+        condition: Box<Expression>,
+        then_branch: Box<Expression>,
+        else_branch: Box<Expression>,
+        span: Span,
+    },
+    /// Interpolated string: `f"Total: {total} items"`
+    InterpolatedString {
+        parts: Vec<StringPart>,
+        span: Span,
+    },
+}
+
+impl Expression {
+    /// Returns the source span this expression was parsed from
+    pub fn span(&self) -> Span {
+        match self {
+            Expression::Binary { span, .. }
+            | Expression::Unary { span, .. }
+            | Expression::Call { span, .. }
+            | Expression::Variable { span, .. }
+            | Expression::Literal { span, .. }
+            | Expression::MethodCall { span, .. }
+            | Expression::ArrayLiteral { span, .. }
+            | Expression::ArrayAccess { span, .. }
+            | Expression::MapLiteral { span, .. }
+            | Expression::TupleLiteral { span, .. }
+            | Expression::TupleAccess { span, .. }
+            | Expression::StructLiteral { span, .. }
+            | Expression::EnumVariant { span, .. }
+            | Expression::FieldAccess { span, .. }
+            | Expression::Conditional { span, .. }
+            | Expression::InterpolatedString { span, .. } => *span,
+        }
+    }
+}
+
+/// A piece of an interpolated string
+#[derive(Debug, Clone)]
+pub enum StringPart {
+    /// Text copied into the result as is
+    Text(String),
+    /// Expression whose value is formatted the same way `paneer.bol` prints it
+    Expr(Expression),
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -106,12 +442,26 @@ pub enum BinaryOperator {
     Subtract,
     Multiply,
     Divide,
+    Modulo,
+    Power,
+    BitAnd,
+    BitOr,
+    BitXor,
+    ShiftLeft,
+    ShiftRight,
     Equal,
     NotEqual,
     Greater,
     Less,
     GreaterEqual,
     LessEqual,
+    /// Logical and (`&&` / `aur`), short-circuiting
+    And,
+    /// Logical or (`||` / `ya`), short-circuiting
+    Or,
+    /// Optional default (`??`): the left value unless it is `khaali`,
+    /// short-circuiting
+    Coalesce,
 }
 
 #[derive(Debug, Clone)]
@@ -127,6 +477,25 @@ pub enum LiteralValue {
     String(String),
     Bool(bool),
     Array(Vec<LiteralValue>),
+    /// Map value, with entries in insertion order
+    Map(Vec<(LiteralValue, LiteralValue)>),
+    /// Tuple value
+    Tuple(Vec<LiteralValue>),
+    /// The missing value of an optional (`khaali`)
+    Khaali,
+    /// The result of a function that returns nothing (`kuch_nahi`)
+    Unit,
+    /// Struct value, with fields in declaration order
+    Struct {
+        name: String,
+        fields: Vec<(String, LiteralValue)>,
+    },
+    /// Enum value, with its variant's payload
+    Enum {
+        name: String,
+        variant: String,
+        payload: Vec<LiteralValue>,
+    },
 }
 
 impl LiteralValue {
@@ -144,14 +513,56 @@ impl LiteralValue {
                     Type::Array(Box::new(arr[0].get_type()))
                 }
             }
+            LiteralValue::Map(entries) => match entries.first() {
+                Some((key, value)) => {
+                    Type::Map(Box::new(key.get_type()), Box::new(value.get_type()))
+                }
+                // Default to map<int, int>, like empty arrays
+                None => Type::Map(Box::new(Type::Int), Box::new(Type::Int)),
+            },
+            LiteralValue::Tuple(elements) => {
+                Type::Tuple(elements.iter().map(LiteralValue::get_type).collect())
+            }
+            LiteralValue::Struct { name, .. } | LiteralValue::Enum { name, .. } => {
+                Type::Named(name.clone())
+            }
+            // `khaali` fits any optional, so it defaults to int? like empty arrays
+            LiteralValue::Khaali => Type::Optional(Box::new(Type::Int)),
+            LiteralValue::Unit => Type::Unit,
+        }
+    }
+
+    /// Whether this value can be stored where `expected` is declared
+    ///
+    /// Empty arrays and maps fit any array or map type, since their element
+    /// types cannot be told from the value itself. An optional type accepts
+    /// `khaali` as well as any value of its inner type.
+    pub fn fits(&self, expected: &Type) -> bool {
+        match (self, expected) {
+            (LiteralValue::Khaali, Type::Optional(_)) => true,
+            (value, Type::Optional(inner)) => value.fits(inner),
+            (LiteralValue::Array(arr), Type::Array(inner)) => {
+                arr.iter().all(|element| element.fits(inner))
+            }
+            (LiteralValue::Map(entries), Type::Map(key_type, value_type)) => entries
+                .iter()
+                .all(|(key, value)| key.fits(key_type) && value.fits(value_type)),
+            (LiteralValue::Tuple(elements), Type::Tuple(types)) => {
+                elements.len() == types.len()
+                    && elements
+                        .iter()
+                        .zip(types)
+                        .all(|(element, t)| element.fits(t))
+            }
+            _ => self.get_type() == *expected,
         }
     }
 
     /// Determines if this value is considered "truthy" in boolean contexts
     ///
     /// # Returns
-    /// * `true` for non-zero numbers, non-empty strings/arrays, and boolean true
-    /// * `false` for zero, empty strings/arrays, and boolean false
+    /// * `true` for non-zero numbers, non-empty strings/arrays/maps, tuples, structs, enums, and boolean true
+    /// * `false` for zero, empty strings/arrays/maps, `khaali`, `kuch_nahi`, and boolean false
     pub fn is_truthy(&self) -> bool {
         match self {
             LiteralValue::Bool(b) => *b,
@@ -159,21 +570,70 @@ impl LiteralValue {
             LiteralValue::Float(f) => *f != 0.0,
             LiteralValue::String(s) => !s.is_empty(),
             LiteralValue::Array(arr) => !arr.is_empty(),
+            LiteralValue::Map(entries) => !entries.is_empty(),
+            LiteralValue::Tuple(_) => true,
+            LiteralValue::Khaali | LiteralValue::Unit => false,
+            LiteralValue::Struct { .. } | LiteralValue::Enum { .. } => true,
         }
     }
 }
 
+/// Formats a float the way PaneerLang prints it
+///
+/// Very large and very small magnitudes switch to exponent notation, so
+/// `6.02e23` prints as written instead of as 24 digits.
+pub fn format_float(value: f64) -> String {
+    let magnitude = value.abs();
+    if magnitude != 0.0 && !(1e-6..1e16).contains(&magnitude) {
+        format!("{:e}", value)
+    } else {
+        value.to_string()
+    }
+}
+
 impl std::fmt::Display for LiteralValue {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             LiteralValue::Int(i) => write!(f, "{}", i),
-            LiteralValue::Float(fl) => write!(f, "{}", fl),
+            LiteralValue::Float(fl) => write!(f, "{}", format_float(*fl)),
             LiteralValue::String(s) => write!(f, "{}", s),
             LiteralValue::Bool(b) => write!(f, "{}", b),
             LiteralValue::Array(arr) => {
                 let elements: Vec<String> = arr.iter().map(|v| v.to_string()).collect();
                 write!(f, "[{}]", elements.join(", "))
             }
+            LiteralValue::Map(entries) => {
+                let entries: Vec<String> = entries
+                    .iter()
+                    .map(|(key, value)| format!("{}: {}", key, value))
+                    .collect();
+                write!(f, "{{{}}}", entries.join(", "))
+            }
+            LiteralValue::Tuple(elements) => {
+                let elements: Vec<String> = elements.iter().map(|v| v.to_string()).collect();
+                write!(f, "({})", elements.join(", "))
+            }
+            LiteralValue::Khaali => write!(f, "khaali"),
+            LiteralValue::Unit => write!(f, "kuch_nahi"),
+            LiteralValue::Struct { name, fields } => {
+                let fields: Vec<String> = fields
+                    .iter()
+                    .map(|(field, value)| format!("{}: {}", field, value))
+                    .collect();
+                write!(f, "{} {{ {} }}", name, fields.join(", "))
+            }
+            LiteralValue::Enum {
+                name,
+                variant,
+                payload,
+            } => {
+                write!(f, "{}::{}", name, variant)?;
+                if !payload.is_empty() {
+                    let values: Vec<String> = payload.iter().map(|v| v.to_string()).collect();
+                    write!(f, "({})", values.join(", "))?;
+                }
+                Ok(())
+            }
         }
     }
 }
@@ -186,6 +646,14 @@ impl std::fmt::Display for Type {
             Type::String => write!(f, "string"),
             Type::Bool => write!(f, "bool"),
             Type::Array(inner) => write!(f, "array<{}>", inner),
+            Type::Map(key, value) => write!(f, "map<{}, {}>", key, value),
+            Type::Tuple(elements) => {
+                let elements: Vec<String> = elements.iter().map(|t| t.to_string()).collect();
+                write!(f, "({})", elements.join(", "))
+            }
+            Type::Optional(inner) => write!(f, "{}?", inner),
+            Type::Unit => write!(f, "kuch_nahi"),
+            Type::Named(name) => write!(f, "{}", name),
         }
     }
 }
@@ -115,6 +115,32 @@ impl DebugInfo {
         println!();
     }
 
+    pub fn print_typeck_info(&self, error_count: usize) {
+        if !self.enabled {
+            return;
+        }
+
+        println!(
+            "{}",
+            PaneerColors::debug_phase("=== TYPE CHECKER ANALYSIS ===")
+        );
+
+        if error_count == 0 {
+            println!(
+                "{} {}",
+                PaneerColors::debug_success("✅ Type Checker:"),
+                PaneerColors::debug_success("PASSED")
+            );
+        } else {
+            println!(
+                "{} {}",
+                PaneerColors::error("❌ Type Checker:"),
+                PaneerColors::error(&format!("FAILED - {} error(s)", error_count))
+            );
+        }
+        println!();
+    }
+
     pub fn print_interpreter_info(&self, success: bool) {
         if !self.enabled {
             return;
@@ -156,19 +182,43 @@ impl DebugInfo {
         for (i, stmt) in program.statements.iter().enumerate() {
             let stmt_type = match stmt {
                 Statement::VarDecl { .. } => "Variable Declaration",
+                Statement::TupleDecl { .. } => "Tuple Declaration",
                 Statement::FuncDecl { .. } => "Function Declaration",
+                Statement::StructDecl { .. } => "Struct Declaration",
+                Statement::EnumDecl { .. } => "Enum Declaration",
                 Statement::ExprStmt { .. } => "Expression Statement",
+                Statement::Assignment { operator: None, .. } => "Assignment",
+                Statement::Assignment { .. } => "Compound Assignment",
                 Statement::IfStmt { .. } => "If Statement",
+                Statement::IfLetStmt { .. } => "If Pattern Statement",
                 Statement::ReturnStmt { .. } => "Return Statement",
+                Statement::BreakStmt { .. } => "Break Statement",
+                Statement::ContinueStmt { .. } => "Continue Statement",
                 Statement::WhileStmt { .. } => "While Loop",
                 Statement::ForStmt { .. } => "For Loop",
+                Statement::RangeForStmt { .. } => "Range Loop",
+                Statement::MatchStmt { .. } => "Match Statement",
+            };
+
+            // Show else-if chains as a single statement with its arm count
+            let (else_ifs, has_else) = else_if_arms(stmt);
+            let label = if else_ifs > 0 {
+                format!(
+                    "{} ({} varna agar arm{}{})",
+                    stmt_type,
+                    else_ifs,
+                    if else_ifs == 1 { "" } else { "s" },
+                    if has_else { " + varna" } else { "" }
+                )
+            } else {
+                stmt_type.to_string()
             };
 
             println!(
                 "  {} {} {}",
                 PaneerColors::number_literal(&format!("{}:", i + 1)),
                 PaneerColors::debug_info("→"),
-                PaneerColors::highlight(stmt_type)
+                PaneerColors::highlight(&label)
             );
         }
         println!();
@@ -205,3 +255,21 @@ impl DebugInfo {
         println!();
     }
 }
+
+/// Counts the `varna agar` arms of an if statement and whether it ends in a
+/// plain `varna` block
+fn else_if_arms(stmt: &Statement) -> (usize, bool) {
+    match stmt {
+        Statement::IfStmt {
+            else_branch: Some(else_stmts),
+            ..
+        } => match else_stmts.as_slice() {
+            [nested @ Statement::IfStmt { .. }] => {
+                let (arms, has_else) = else_if_arms(nested);
+                (arms + 1, has_else)
+            }
+            _ => (0, true),
+        },
+        _ => (0, false),
+    }
+}
@@ -0,0 +1,139 @@
+//! Source locations for error reporting
+//!
+//! Converts byte spans into line/column positions and renders the offending
+//! source line with a caret underline.
+
+use crate::ast::Span;
+use crate::errors::Warning;
+use crate::utils::colors::PaneerColors;
+use unicode_width::UnicodeWidthStr;
+
+/// A 1-based line and column position in the source code
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LineColumn {
+    pub line: usize,
+    pub column: usize,
+}
+
+impl LineColumn {
+    /// Converts a byte offset into a line and column, counting columns in characters
+    pub fn from_offset(source: &str, offset: usize) -> Self {
+        let offset = clamp_to_char_boundary(source, offset);
+        let before = &source[..offset];
+        let line = before.matches('\n').count() + 1;
+        let line_start = before.rfind('\n').map_or(0, |pos| pos + 1);
+        let column = source[line_start..offset].chars().count() + 1;
+        LineColumn { line, column }
+    }
+}
+
+impl std::fmt::Display for LineColumn {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}", self.line, self.column)
+    }
+}
+
+/// Renders the first source line covered by `span` with the span underlined
+///
+/// ```text
+///   3 │ ye x: int = "hello";
+///     │             ^^^^^^^
+/// ```
+pub fn render_snippet(source: &str, span: Span) -> String {
+    let start = clamp_to_char_boundary(source, span.start);
+    let end = clamp_to_char_boundary(source, span.end.max(start));
+    let location = LineColumn::from_offset(source, start);
+
+    let line_start = source[..start].rfind('\n').map_or(0, |pos| pos + 1);
+    let line_end = source[start..]
+        .find('\n')
+        .map_or(source.len(), |pos| start + pos);
+    let line_text = source[line_start..line_end].trim_end_matches('\r');
+
+    // Underline at least one column, and never past the end of the line.
+    // Widths skip combining marks, such as the vowel sign in `नाम`, and count
+    // emoji and CJK characters as two columns so the carets stay aligned.
+    // Tabs are copied as they are, since the terminal decides how wide they
+    // are.
+    let indent = source[line_start..start]
+        .split('\t')
+        .map(|text| " ".repeat(display_width(text)))
+        .collect::<Vec<_>>()
+        .join("\t");
+    let underline_len = display_width(&source[start..end.min(line_end)]).max(1);
+
+    let gutter = location.line.to_string();
+    let padding = " ".repeat(gutter.len());
+
+    format!(
+        "{} {} {}\n{} {} {}{}",
+        PaneerColors::number_literal(&gutter),
+        PaneerColors::border("│"),
+        line_text,
+        padding,
+        PaneerColors::border("│"),
+        indent,
+        PaneerColors::error(&"^".repeat(underline_len))
+    )
+}
+
+/// Formats a warning with its position and the source line it refers to
+pub fn format_warning(warning: &Warning, source: &str) -> String {
+    let location = LineColumn::from_offset(source, warning.span.start);
+    format!(
+        "{} {}\n{} {}\n{}\n",
+        PaneerColors::warning("⚠️  Warning:"),
+        warning.message,
+        PaneerColors::info("📍 Line:"),
+        PaneerColors::number_literal(&format!("{}, Column {}", location.line, location.column)),
+        render_snippet(source, warning.span)
+    )
+}
+
+/// Returns the number of terminal columns `text` occupies
+fn display_width(text: &str) -> usize {
+    UnicodeWidthStr::width(text)
+}
+
+/// Moves `offset` back to the nearest character boundary inside `source`
+fn clamp_to_char_boundary(source: &str, offset: usize) -> usize {
+    let mut offset = offset.min(source.len());
+    while !source.is_char_boundary(offset) {
+        offset -= 1;
+    }
+    offset
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Returns the caret line of the snippet rendered for `needle` in `source`
+    fn carets(source: &str, needle: &str) -> String {
+        colored::control::set_override(false);
+        let start = source.find(needle).unwrap();
+        let snippet = render_snippet(source, Span::new(start, start + needle.len()));
+        snippet.lines().nth(1).unwrap().to_string()
+    }
+
+    #[test]
+    fn carets_line_up_under_wide_and_combining_characters() {
+        assert_eq!(
+            carets(r#"paneer.bol("🧀🧀🧀" + zz);"#, "zz"),
+            format!("  │ {}^^", " ".repeat(22))
+        );
+        assert_eq!(
+            carets(r#"ye नाम: int = "पनीर";"#, "int"),
+            format!("  │ {}^^^", " ".repeat(8))
+        );
+    }
+
+    #[test]
+    fn carets_keep_the_tabs_of_the_line() {
+        assert_eq!(
+            carets("\t\tye x: int = zz;", "zz"),
+            "  │ \t\t            ^^"
+        );
+        assert_eq!(carets("ye\tx: int = zz;", "zz"), "  │   \t         ^^");
+    }
+}
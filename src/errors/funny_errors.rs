@@ -1,3 +1,5 @@
+use crate::errors::PaneerError;
+use crate::errors::diagnostics::{LineColumn, render_snippet};
 use colored::*;
 use rand::Rng;
 
@@ -90,20 +92,13 @@ impl FunnyErrorGenerator {
         self.encouragements[rng.random_range(0..self.encouragements.len())]
     }
 
-    pub fn format_error(
+    /// Formats every error from one REPL input under a single header
+    pub fn format_errors(
         &self,
-        error_type: &str,
-        original_error: &str,
+        errors: &[PaneerError],
         file: Option<&str>,
-        line: Option<usize>,
+        source: Option<&str>,
     ) -> String {
-        let funny_message = match error_type.to_lowercase().as_str() {
-            "syntax" | "parse" => self.get_funny_syntax_error(),
-            "type" => self.get_funny_type_error(),
-            "undefined" | "variable" => self.get_funny_undefined_error(),
-            _ => self.get_funny_general_error(),
-        };
-
         let encouragement = self.get_encouragement();
 
         let mut result = String::new();
@@ -123,30 +118,46 @@ impl FunnyErrorGenerator {
         ));
         result.push('\n');
 
-        // Funny message
-        result.push_str(&format!("{} {}\n", "😅".yellow(), funny_message.yellow()));
-        result.push('\n');
-
-        // Technical details
-        result.push_str(&format!(
-            "{} {}\n",
-            "🔍 Technical Details:".red().bold(),
-            original_error.white()
-        ));
-
-        if let Some(file_name) = file {
-            result.push_str(&format!("{} {}\n", "📁 File:".blue(), file_name.cyan()));
-        }
-
-        if let Some(line_num) = line {
+        for error in errors {
+            let funny_message = match error {
+                PaneerError::Lex { .. }
+                | PaneerError::Syntax { .. }
+                | PaneerError::LoopControl { .. } => self.get_funny_syntax_error(),
+                PaneerError::Type { .. } | PaneerError::Arity { .. } => self.get_funny_type_error(),
+                PaneerError::UndefinedName { .. } => self.get_funny_undefined_error(),
+                _ => self.get_funny_general_error(),
+            };
+            let location = source.zip(error.span());
+
+            // Funny message
+            result.push_str(&format!("{} {}\n", "😅".yellow(), funny_message.yellow()));
+            result.push('\n');
+
+            // Technical details
             result.push_str(&format!(
                 "{} {}\n",
-                "📍 Line:".blue(),
-                line_num.to_string().cyan()
+                "🔍 Technical Details:".red().bold(),
+                format!("[{}] {}", error.code(), error).white()
             ));
-        }
 
-        result.push('\n');
+            if let Some(file_name) = file {
+                result.push_str(&format!("{} {}\n", "📁 File:".blue(), file_name.cyan()));
+            }
+
+            if let Some((source, span)) = location {
+                let position = LineColumn::from_offset(source, span.start);
+                result.push_str(&format!(
+                    "{} {}\n",
+                    "📍 Line:".blue(),
+                    format!("{}, Column {}", position.line, position.column).cyan()
+                ));
+                result.push('\n');
+                result.push_str(&render_snippet(source, span));
+                result.push('\n');
+            }
+
+            result.push('\n');
+        }
 
         // Encouragement
         result.push_str(&format!("{} {}\n", "💡".green(), encouragement.green()));
@@ -1,3 +1,6 @@
+use crate::ast::BinaryOperator;
+use crate::errors::diagnostics::{LineColumn, render_snippet};
+use crate::errors::{Expected, PaneerError};
 use crate::utils::colors::PaneerColors;
 use rand::Rng;
 
@@ -8,30 +11,37 @@ impl HinglishErrorGenerator {
         HinglishErrorGenerator
     }
 
-    pub fn translate_error(&self, original_error: &str) -> String {
+    pub fn translate_error(&self, error: &PaneerError) -> String {
         // Convert technical errors to Hinglish
-        if original_error.contains("Expected ';'") {
-            self.get_semicolon_error()
-        } else if original_error.contains("Expected '}'") {
-            self.get_brace_error()
-        } else if original_error.contains("Expected ')'") {
-            self.get_paren_error()
-        } else if original_error.contains("Undefined variable") {
-            self.get_undefined_var_error()
-        } else if original_error.contains("Type mismatch") {
-            self.get_type_error()
-        } else if original_error.contains("Expected expression") {
-            self.get_expression_error()
-        } else if original_error.contains("Expected variable name") {
-            self.get_var_name_error()
-        } else if original_error.contains("Expected function name") {
-            self.get_func_name_error()
-        } else if original_error.contains("Division by zero") {
-            self.get_division_error()
-        } else if original_error.contains("Array index out of bounds") {
-            self.get_array_bounds_error()
-        } else {
-            self.get_general_error()
+        match error {
+            PaneerError::Syntax {
+                expected: Some(expected),
+                ..
+            } => match expected {
+                Expected::Semicolon => self.get_semicolon_error(),
+                Expected::ClosingBrace => self.get_brace_error(),
+                Expected::ClosingParen => self.get_paren_error(),
+                Expected::Expression => self.get_expression_error(),
+                Expected::VariableName => self.get_var_name_error(),
+                Expected::FunctionName => self.get_func_name_error(),
+            },
+            PaneerError::UndefinedName { .. } => self.get_undefined_var_error(),
+            PaneerError::Type { .. } => self.get_type_error(),
+            PaneerError::DivisionByZero { .. } => self.get_division_error(),
+            PaneerError::InvalidOperand {
+                operator: BinaryOperator::Power,
+                ..
+            } => self.get_exponent_error(),
+            PaneerError::InvalidOperand { .. } => self.get_shift_error(),
+            PaneerError::Overflow { .. } => self.get_overflow_error(),
+            PaneerError::IndexOutOfBounds { .. } => self.get_array_bounds_error(),
+            PaneerError::KeyNotFound { .. } => self.get_missing_key_error(),
+            PaneerError::LoopControl {
+                in_function_in_loop: true,
+                ..
+            } => self.get_function_in_loop_control_error(),
+            PaneerError::LoopControl { .. } => self.get_loop_control_error(),
+            _ => self.get_general_error(),
         }
     }
 
@@ -134,6 +144,39 @@ impl HinglishErrorGenerator {
         self.random_message(&messages)
     }
 
+    fn get_shift_error(&self) -> String {
+        let messages = vec![
+            "Shift amount galat hai! Bits ko 0 se 63 tak hi shift kar sakte ho.",
+            "Negative shift nahi hota bhai! << aur >> ke right side pe positive number do.",
+            "Itna shift karoge toh saare bits gayab ho jayenge! 0 se 63 ke beech rakho.",
+            "Shift error! Shift amount negative ya 63 se zyada nahi hona chahiye.",
+            "Arre bits ko ulta shift nahi kar sakte! Shift amount check karo.",
+        ];
+        self.random_message(&messages)
+    }
+
+    fn get_exponent_error(&self) -> String {
+        let messages = vec![
+            "Int ki negative power nahi nikal sakte! Float use karo ya exponent positive rakho.",
+            "Negative exponent int ke saath allowed nahi hai! Jaise 2.0 ** -1.0 likho.",
+            "Arre 2 ** -1 ka answer int mein nahi aata! Float try karo.",
+            "Power error! Int ke liye exponent 0 ya usse bada hona chahiye.",
+            "Negative power ka result fraction hota hai - int mein fit nahi hoga.",
+        ];
+        self.random_message(&messages)
+    }
+
+    fn get_overflow_error(&self) -> String {
+        let messages = vec![
+            "Number bahut bada ho gaya! Int ki limit cross ho gayi.",
+            "Integer overflow! Result int mein fit nahi ho raha.",
+            "Arre itna bada number? Int 64-bit mein nahi samayega.",
+            "Overflow ho gaya bhai! Chhote numbers use karo ya float try karo.",
+            "Calculation ka result int ki range se bahar chala gaya!",
+        ];
+        self.random_message(&messages)
+    }
+
     fn get_array_bounds_error(&self) -> String {
         let messages = vec![
             "Array index out of range! Array ke size se zyada index access kar rahe ho.",
@@ -145,6 +188,36 @@ impl HinglishErrorGenerator {
         self.random_message(&messages)
     }
 
+    fn get_missing_key_error(&self) -> String {
+        let messages = vec![
+            "Ye key map mein hai hi nahi! Pehle 'has' se check karo ya key insert karo.",
+            "Map mein aisi koi key nahi mili! Spelling check karo bhai.",
+            "Key missing hai! Map se wahi key nikalo jo pehle daali thi.",
+            "Arre ye key kabhi insert hi nahi hui! 'm[key] = value;' se pehle daalo.",
+            "Map lookup fail! Key exist karti hai ya nahi, 'has' se dekh lo.",
+        ];
+        self.random_message(&messages)
+    }
+
+    fn get_loop_control_error(&self) -> String {
+        let messages = vec![
+            "'ruko' aur 'aage badho' sirf loop ke andar chalte hain! Pehle jabtak ya har lagao.",
+            "Bhai loop hai hi nahi, toh rukoge kahan se? 'ruko' loop ke andar use karo.",
+            "Loop ke bahar 'ruko' ya 'aage badho' allowed nahi hai!",
+            "Arre ye loop control statement loop ke bahar hai! Jabtak ya har ke andar rakho.",
+        ];
+        self.random_message(&messages)
+    }
+
+    fn get_function_in_loop_control_error(&self) -> String {
+        let messages = vec![
+            "Function ke andar se bahar wale loop ko 'ruko' nahi kar sakte!",
+            "Function ki deewar loop tak nahi jaane deti! Value wapas karo aur loop mein ruko.",
+            "Ye loop function ke bahar hai! 'ruko' ya 'aage badho' function ke apne loop mein hi chalte hain.",
+        ];
+        self.random_message(&messages)
+    }
+
     fn get_general_error(&self) -> String {
         let messages = vec![
             "Kuch toh gadbad hai! Code check karo aur phir se try karo.",
@@ -164,12 +237,20 @@ impl HinglishErrorGenerator {
 
     pub fn format_hinglish_error(
         &self,
-        original_error: &str,
+        error: &PaneerError,
         file: Option<&str>,
-        line: Option<usize>,
+        source: Option<&str>,
     ) -> String {
-        let hinglish_msg = self.translate_error(original_error);
+        self.format_hinglish_errors(std::slice::from_ref(error), file, source)
+    }
 
+    /// Formats every error from one run under a single header
+    pub fn format_hinglish_errors(
+        &self,
+        errors: &[PaneerError],
+        file: Option<&str>,
+        source: Option<&str>,
+    ) -> String {
         let mut result = String::new();
 
         // Error header
@@ -187,32 +268,61 @@ impl HinglishErrorGenerator {
         ));
         result.push('\n');
 
-        // Hinglish message
-        result.push_str(&format!(
-            "{} {}\n",
-            "💬",
-            PaneerColors::warning(&hinglish_msg)
-        ));
-        result.push('\n');
-
-        // File info
-        if let Some(file_name) = file {
+        if errors.len() > 1 {
             result.push_str(&format!(
-                "{} {}\n",
-                PaneerColors::info("📁 File:"),
-                PaneerColors::highlight(file_name)
+                "{}\n\n",
+                PaneerColors::error(&format!("❗ {} errors mile:", errors.len()))
             ));
         }
 
-        if let Some(line_num) = line {
+        for error in errors {
+            let hinglish_msg = self.translate_error(error);
+            let location = source.zip(error.span());
+
+            // Hinglish message
             result.push_str(&format!(
                 "{} {}\n",
-                PaneerColors::info("📍 Line:"),
-                PaneerColors::number_literal(&line_num.to_string())
+                "💬",
+                PaneerColors::warning(&hinglish_msg)
             ));
-        }
+            result.push('\n');
 
-        result.push('\n');
+            // File info
+            if let Some(file_name) = file {
+                result.push_str(&format!(
+                    "{} {}\n",
+                    PaneerColors::info("📁 File:"),
+                    PaneerColors::highlight(file_name)
+                ));
+            }
+
+            if let Some((source, span)) = location {
+                let position = LineColumn::from_offset(source, span.start);
+                result.push_str(&format!(
+                    "{} {}\n",
+                    PaneerColors::info("📍 Line:"),
+                    PaneerColors::number_literal(&format!(
+                        "{}, Column {}",
+                        position.line, position.column
+                    ))
+                ));
+            }
+
+            result.push_str(&format!(
+                "{} [{}] {}\n",
+                PaneerColors::info("🔍 Details:"),
+                error.code(),
+                error
+            ));
+
+            if let Some((source, span)) = location {
+                result.push('\n');
+                result.push_str(&render_snippet(source, span));
+                result.push('\n');
+            }
+
+            result.push('\n');
+        }
 
         // Quick fix suggestions
         result.push_str(&format!("{}\n", PaneerColors::info("💡 Quick Fix:")));
@@ -233,3 +343,17 @@ impl HinglishErrorGenerator {
         result
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_functions_inside_loops_blame_the_function() {
+        let generator = HinglishErrorGenerator::new();
+        let top_level = PaneerError::loop_control("'ruko' can only be used inside a loop", false);
+        for _ in 0..50 {
+            assert!(!generator.translate_error(&top_level).contains("Function"));
+        }
+    }
+}
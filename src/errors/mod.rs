@@ -1,2 +1,8 @@
+pub mod diagnostics;
 pub mod funny_errors;
 pub mod hinglish_errors;
+pub mod paneer_error;
+pub mod warning;
+
+pub use paneer_error::{Expected, NameKind, PaneerError, Result};
+pub use warning::Warning;
@@ -0,0 +1,294 @@
+//! Structured errors produced by the lexer, parser and interpreter
+//!
+//! Every error belongs to a `PaneerError` variant with a stable error code, so
+//! the error generators can pick a message by matching on the variant instead
+//! of searching the text of the message.
+
+use crate::ast::{BinaryOperator, Span};
+
+/// Result type used throughout the PaneerLang pipeline
+pub type Result<T> = std::result::Result<T, PaneerError>;
+
+/// What the parser was looking for when it hit a syntax error
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Expected {
+    Semicolon,
+    ClosingBrace,
+    ClosingParen,
+    Expression,
+    VariableName,
+    FunctionName,
+}
+
+/// Whether an undefined name referred to a variable or a function
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NameKind {
+    Variable,
+    Function,
+}
+
+/// An error raised while lexing, parsing or running a PaneerLang program
+///
+/// `span` is `None` until the error is attached to the source it came from.
+#[derive(Debug, Clone)]
+pub enum PaneerError {
+    /// Text the lexer could not turn into a token
+    Lex { message: String, span: Option<Span> },
+    /// Malformed syntax
+    Syntax {
+        message: String,
+        expected: Option<Expected>,
+        span: Option<Span>,
+    },
+    /// `ruko` or `aage badho` used outside of a loop
+    ///
+    /// `in_function_in_loop` is set when the statement sits in a function
+    /// declared inside a loop, whose loop it cannot reach.
+    LoopControl {
+        message: String,
+        in_function_in_loop: bool,
+        span: Option<Span>,
+    },
+    /// A value of the wrong type
+    Type { message: String, span: Option<Span> },
+    /// Use of a variable or function that was never defined
+    UndefinedName {
+        kind: NameKind,
+        name: String,
+        span: Option<Span>,
+    },
+    /// A call with the wrong number of arguments
+    Arity {
+        name: String,
+        expected: usize,
+        found: usize,
+        span: Option<Span>,
+    },
+    /// An array index outside the array
+    IndexOutOfBounds { index: i64, span: Option<Span> },
+    /// A map lookup for a key the map does not contain
+    KeyNotFound { key: String, span: Option<Span> },
+    /// Division or modulo by zero
+    DivisionByZero { message: String, span: Option<Span> },
+    /// An integer result that does not fit in 64 bits
+    Overflow { message: String, span: Option<Span> },
+    /// A right-hand operand the operator cannot accept, such as a negative
+    /// exponent or shift amount
+    InvalidOperand {
+        operator: BinaryOperator,
+        message: String,
+        span: Option<Span>,
+    },
+    /// Any other failure while running the program
+    Runtime { message: String, span: Option<Span> },
+    /// A file that could not be read
+    Io { message: String, span: Option<Span> },
+}
+
+impl PaneerError {
+    pub fn lex(message: impl Into<String>) -> Self {
+        PaneerError::Lex {
+            message: message.into(),
+            span: None,
+        }
+    }
+
+    pub fn syntax(message: impl Into<String>) -> Self {
+        PaneerError::Syntax {
+            message: message.into(),
+            expected: None,
+            span: None,
+        }
+    }
+
+    pub fn expected(expected: Expected, message: impl Into<String>) -> Self {
+        PaneerError::Syntax {
+            message: message.into(),
+            expected: Some(expected),
+            span: None,
+        }
+    }
+
+    pub fn loop_control(message: impl Into<String>, in_function_in_loop: bool) -> Self {
+        PaneerError::LoopControl {
+            message: message.into(),
+            in_function_in_loop,
+            span: None,
+        }
+    }
+
+    pub fn type_error(message: impl Into<String>) -> Self {
+        PaneerError::Type {
+            message: message.into(),
+            span: None,
+        }
+    }
+
+    pub fn undefined(kind: NameKind, name: impl Into<String>) -> Self {
+        PaneerError::UndefinedName {
+            kind,
+            name: name.into(),
+            span: None,
+        }
+    }
+
+    pub fn arity(name: impl Into<String>, expected: usize, found: usize) -> Self {
+        PaneerError::Arity {
+            name: name.into(),
+            expected,
+            found,
+            span: None,
+        }
+    }
+
+    pub fn index_out_of_bounds(index: i64) -> Self {
+        PaneerError::IndexOutOfBounds { index, span: None }
+    }
+
+    pub fn key_not_found(key: impl Into<String>) -> Self {
+        PaneerError::KeyNotFound {
+            key: key.into(),
+            span: None,
+        }
+    }
+
+    pub fn division_by_zero(message: impl Into<String>) -> Self {
+        PaneerError::DivisionByZero {
+            message: message.into(),
+            span: None,
+        }
+    }
+
+    pub fn overflow(message: impl Into<String>) -> Self {
+        PaneerError::Overflow {
+            message: message.into(),
+            span: None,
+        }
+    }
+
+    pub fn invalid_operand(operator: BinaryOperator, message: impl Into<String>) -> Self {
+        PaneerError::InvalidOperand {
+            operator,
+            message: message.into(),
+            span: None,
+        }
+    }
+
+    pub fn runtime(message: impl Into<String>) -> Self {
+        PaneerError::Runtime {
+            message: message.into(),
+            span: None,
+        }
+    }
+
+    pub fn io(message: impl Into<String>) -> Self {
+        PaneerError::Io {
+            message: message.into(),
+            span: None,
+        }
+    }
+
+    /// Returns the stable code identifying this kind of error
+    pub fn code(&self) -> &'static str {
+        match self {
+            PaneerError::Lex { .. } => "E0001",
+            PaneerError::Syntax { .. } => "E0002",
+            PaneerError::LoopControl { .. } => "E0003",
+            PaneerError::Type { .. } => "E0101",
+            PaneerError::UndefinedName { .. } => "E0102",
+            PaneerError::Arity { .. } => "E0103",
+            PaneerError::IndexOutOfBounds { .. } => "E0201",
+            PaneerError::DivisionByZero { .. } => "E0202",
+            PaneerError::Overflow { .. } => "E0203",
+            PaneerError::InvalidOperand { .. } => "E0204",
+            PaneerError::KeyNotFound { .. } => "E0205",
+            PaneerError::Runtime { .. } => "E0299",
+            PaneerError::Io { .. } => "E0301",
+        }
+    }
+
+    /// Returns the location of the error in the source, if known
+    pub fn span(&self) -> Option<Span> {
+        match self {
+            PaneerError::Lex { span, .. }
+            | PaneerError::Syntax { span, .. }
+            | PaneerError::LoopControl { span, .. }
+            | PaneerError::Type { span, .. }
+            | PaneerError::UndefinedName { span, .. }
+            | PaneerError::Arity { span, .. }
+            | PaneerError::IndexOutOfBounds { span, .. }
+            | PaneerError::KeyNotFound { span, .. }
+            | PaneerError::DivisionByZero { span, .. }
+            | PaneerError::Overflow { span, .. }
+            | PaneerError::InvalidOperand { span, .. }
+            | PaneerError::Runtime { span, .. }
+            | PaneerError::Io { span, .. } => *span,
+        }
+    }
+
+    /// Points the error at `span`
+    pub fn at(mut self, span: Span) -> Self {
+        *self.span_slot() = Some(span);
+        self
+    }
+
+    /// Points the error at `span` unless it already has a more precise location
+    pub fn or_at(mut self, span: Span) -> Self {
+        self.span_slot().get_or_insert(span);
+        self
+    }
+
+    fn span_slot(&mut self) -> &mut Option<Span> {
+        match self {
+            PaneerError::Lex { span, .. }
+            | PaneerError::Syntax { span, .. }
+            | PaneerError::LoopControl { span, .. }
+            | PaneerError::Type { span, .. }
+            | PaneerError::UndefinedName { span, .. }
+            | PaneerError::Arity { span, .. }
+            | PaneerError::IndexOutOfBounds { span, .. }
+            | PaneerError::KeyNotFound { span, .. }
+            | PaneerError::DivisionByZero { span, .. }
+            | PaneerError::Overflow { span, .. }
+            | PaneerError::InvalidOperand { span, .. }
+            | PaneerError::Runtime { span, .. }
+            | PaneerError::Io { span, .. } => span,
+        }
+    }
+}
+
+impl std::fmt::Display for PaneerError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PaneerError::Lex { message, .. }
+            | PaneerError::Syntax { message, .. }
+            | PaneerError::LoopControl { message, .. }
+            | PaneerError::Type { message, .. }
+            | PaneerError::DivisionByZero { message, .. }
+            | PaneerError::Overflow { message, .. }
+            | PaneerError::InvalidOperand { message, .. }
+            | PaneerError::Runtime { message, .. }
+            | PaneerError::Io { message, .. } => write!(f, "{}", message),
+            PaneerError::UndefinedName { kind, name, .. } => match kind {
+                NameKind::Variable => write!(f, "Undefined variable: {}", name),
+                NameKind::Function => write!(f, "Undefined function: {}", name),
+            },
+            PaneerError::Arity {
+                name,
+                expected,
+                found,
+                ..
+            } => write!(
+                f,
+                "Function {} expects {} argument(s), got {}",
+                name, expected, found
+            ),
+            PaneerError::IndexOutOfBounds { index, .. } => {
+                write!(f, "Array index out of bounds: {}", index)
+            }
+            PaneerError::KeyNotFound { key, .. } => write!(f, "Key not found in map: {}", key),
+        }
+    }
+}
+
+impl std::error::Error for PaneerError {}
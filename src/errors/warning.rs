@@ -0,0 +1,25 @@
+//! Warnings about code that runs but probably does not do what was meant
+
+use crate::ast::Span;
+
+/// A problem found by the type checker that does not stop the program
+#[derive(Debug, Clone)]
+pub struct Warning {
+    pub message: String,
+    pub span: Span,
+}
+
+impl Warning {
+    pub fn new(message: impl Into<String>, span: Span) -> Self {
+        Warning {
+            message: message.into(),
+            span,
+        }
+    }
+}
+
+impl std::fmt::Display for Warning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
@@ -4,7 +4,7 @@
 //! It handles variable scoping, function calls, control flow, and built-in operations.
 
 use crate::ast::*;
-use anyhow::{Result, anyhow};
+use crate::errors::{NameKind, PaneerError, Result};
 use std::collections::HashMap;
 
 /// Represents a user-defined function in PaneerLang
@@ -18,13 +18,22 @@ pub struct Function {
     pub body: Vec<Statement>,
 }
 
+/// A variable binding together with the type it was declared with
+#[derive(Debug, Clone)]
+pub struct Variable {
+    /// Declared type, used to check later assignments
+    pub var_type: Type,
+    /// Current value of the variable
+    pub value: LiteralValue,
+}
+
 /// Environment for variable and function scoping
 ///
 /// Supports lexical scoping with parent environments for nested scopes
 #[derive(Debug, Clone)]
 pub struct Environment {
     /// Variables defined in this scope
-    variables: HashMap<String, LiteralValue>,
+    variables: HashMap<String, Variable>,
     /// Functions defined in this scope
     functions: HashMap<String, Function>,
     /// Parent environment for lexical scoping
@@ -50,15 +59,15 @@ impl Environment {
         }
     }
 
-    /// Defines a variable in the current scope
-    pub fn define_variable(&mut self, name: String, value: LiteralValue) {
-        self.variables.insert(name, value);
+    /// Defines a variable with its declared type in the current scope
+    pub fn define_variable(&mut self, name: String, var_type: Type, value: LiteralValue) {
+        self.variables.insert(name, Variable { var_type, value });
     }
 
     /// Retrieves a variable value, checking parent scopes if not found locally
     pub fn get_variable(&self, name: &str) -> Option<LiteralValue> {
-        if let Some(value) = self.variables.get(name) {
-            Some(value.clone())
+        if let Some(variable) = self.variables.get(name) {
+            Some(variable.value.clone())
         } else if let Some(parent) = &self.parent {
             parent.get_variable(name)
         } else {
@@ -66,11 +75,36 @@ impl Environment {
         }
     }
 
+    /// Retrieves a mutable reference to a variable in the scope that defines it
+    pub fn get_variable_mut(&mut self, name: &str) -> Option<&mut Variable> {
+        if let Some(variable) = self.variables.get_mut(name) {
+            Some(variable)
+        } else if let Some(parent) = &mut self.parent {
+            parent.get_variable_mut(name)
+        } else {
+            None
+        }
+    }
+
     /// Defines a function in the current scope
     pub fn define_function(&mut self, name: String, function: Function) {
         self.functions.insert(name, function);
     }
 
+    /// Number of scopes in this environment's chain, counting itself
+    fn depth(&self) -> usize {
+        1 + self.parent.as_ref().map_or(0, |parent| parent.depth())
+    }
+
+    /// Depth of the scope that defines the function `name`
+    fn function_depth(&self, name: &str) -> Option<usize> {
+        if self.functions.contains_key(name) {
+            Some(self.depth())
+        } else {
+            self.parent.as_ref()?.function_depth(name)
+        }
+    }
+
     /// Retrieves a function, checking parent scopes if not found locally
     pub fn get_function(&self, name: &str) -> Option<Function> {
         if let Some(function) = self.functions.get(name) {
@@ -83,10 +117,23 @@ impl Environment {
     }
 }
 
+/// One step from a variable to the part of it an assignment writes
+#[derive(Debug, Clone)]
+enum PlaceStep {
+    /// An array element or map entry
+    Index(LiteralValue),
+    /// A struct field
+    Field(String),
+}
+
 /// Main interpreter struct that executes PaneerLang programs
 pub struct Interpreter {
     /// Current execution environment
     environment: Environment,
+    /// Fields of every struct declared with `dhancha`, in declaration order
+    structs: HashMap<String, Vec<(String, Type)>>,
+    /// Variants of every enum declared with `prakar`, with their payload types
+    enums: HashMap<String, Vec<(String, Vec<Type>)>>,
 }
 
 /// Runtime values that can be returned from statement execution
@@ -96,6 +143,10 @@ pub enum RuntimeValue {
     Value,
     /// Return statement with a value
     Return(LiteralValue),
+    /// `ruko` - leave the innermost loop
+    Break,
+    /// `aage badho` - skip to the next iteration of the innermost loop
+    Continue,
 }
 
 impl Interpreter {
@@ -103,6 +154,8 @@ impl Interpreter {
     pub fn new() -> Self {
         Interpreter {
             environment: Environment::new(),
+            structs: HashMap::new(),
+            enums: HashMap::new(),
         }
     }
 
@@ -113,11 +166,12 @@ impl Interpreter {
     ///
     /// # Returns
     /// * `Ok(())` - If program executes successfully
-    /// * `Err(anyhow::Error)` - If execution fails
+    /// * `Err(PaneerError)` - If execution fails
     pub fn interpret(&mut self, program: Program) -> Result<()> {
         for statement in program.statements {
+            let span = statement.span();
             if let RuntimeValue::Return(_) = self.execute_statement(statement)? {
-                return Err(anyhow!("Return statement outside of function"));
+                return Err(PaneerError::runtime("Return statement outside of function").at(span));
             }
         }
         Ok(())
@@ -130,26 +184,79 @@ impl Interpreter {
     ///
     /// # Returns
     /// * `Ok(RuntimeValue)` - Normal execution or return value
-    /// * `Err(anyhow::Error)` - If execution fails
+    /// * `Err(PaneerError)` - If execution fails, located at the innermost failing node
     fn execute_statement(&mut self, statement: Statement) -> Result<RuntimeValue> {
+        let span = statement.span();
+        self.execute_statement_inner(statement)
+            .map_err(|err| err.or_at(span))
+    }
+
+    fn execute_statement_inner(&mut self, statement: Statement) -> Result<RuntimeValue> {
         match statement {
             Statement::VarDecl {
                 name,
                 type_annotation,
                 initializer,
+                ..
             } => {
+                let initializer_span = initializer.span();
                 let value = self.evaluate_expression(initializer)?;
 
                 // Type checking
-                if value.get_type() != type_annotation {
-                    return Err(anyhow!(
+                if !value.fits(&type_annotation) {
+                    return Err(PaneerError::type_error(format!(
                         "Type mismatch: expected {}, got {}",
                         type_annotation,
                         value.get_type()
-                    ));
+                    ))
+                    .at(initializer_span));
+                }
+
+                self.environment
+                    .define_variable(name, type_annotation, value);
+                Ok(RuntimeValue::Value)
+            }
+
+            Statement::TupleDecl {
+                names,
+                type_annotation,
+                initializer,
+                ..
+            } => {
+                let initializer_span = initializer.span();
+                let value = self.evaluate_expression(initializer)?;
+
+                let (LiteralValue::Tuple(values), Type::Tuple(types)) = (value, &type_annotation)
+                else {
+                    return Err(PaneerError::type_error(format!(
+                        "Can only destructure a tuple, expected {}",
+                        type_annotation
+                    ))
+                    .at(initializer_span));
+                };
+                if names.len() != types.len() || values.len() != types.len() {
+                    return Err(PaneerError::type_error(format!(
+                        "Cannot destructure {} into {} name(s)",
+                        type_annotation,
+                        names.len()
+                    ))
+                    .at(initializer_span));
                 }
 
-                self.environment.define_variable(name, value);
+                for ((name, element_type), element) in names.into_iter().zip(types).zip(values) {
+                    if !element.fits(element_type) {
+                        return Err(PaneerError::type_error(format!(
+                            "Type mismatch: expected {}, got {}",
+                            element_type,
+                            element.get_type()
+                        ))
+                        .at(initializer_span));
+                    }
+                    if name != "_" {
+                        self.environment
+                            .define_variable(name, element_type.clone(), element);
+                    }
+                }
                 Ok(RuntimeValue::Value)
             }
 
@@ -158,6 +265,7 @@ impl Interpreter {
                 params,
                 return_type,
                 body,
+                ..
             } => {
                 let function = Function {
                     params,
@@ -168,177 +276,630 @@ impl Interpreter {
                 Ok(RuntimeValue::Value)
             }
 
-            Statement::ExprStmt { expression } => {
+            Statement::StructDecl { name, fields, .. } => {
+                self.structs.insert(name, fields);
+                Ok(RuntimeValue::Value)
+            }
+
+            Statement::EnumDecl { name, variants, .. } => {
+                self.enums.insert(name, variants);
+                Ok(RuntimeValue::Value)
+            }
+
+            Statement::ExprStmt { expression, .. } => {
                 self.evaluate_expression(expression)?;
                 Ok(RuntimeValue::Value)
             }
 
+            Statement::Assignment {
+                target,
+                operator,
+                value,
+                ..
+            } => {
+                // Resolve the target once so index expressions are evaluated only once
+                let (name, steps) = self.resolve_place(target)?;
+                let value = self.evaluate_expression(value)?;
+
+                let new_value = match operator {
+                    Some(operator) => {
+                        let current = self.read_place(&name, &steps)?;
+                        self.apply_binary_operator(operator, current, value)?
+                    }
+                    None => value,
+                };
+
+                self.assign_place(&name, &steps, new_value)?;
+                Ok(RuntimeValue::Value)
+            }
+
             Statement::IfStmt {
                 condition,
                 then_branch,
                 else_branch,
+                ..
             } => {
                 let condition_value = self.evaluate_expression(condition)?;
 
                 if condition_value.is_truthy() {
-                    for stmt in then_branch {
-                        if let RuntimeValue::Return(val) = self.execute_statement(stmt)? {
-                            return Ok(RuntimeValue::Return(val));
-                        }
-                    }
+                    self.execute_scoped_block(then_branch)
                 } else if let Some(else_stmts) = else_branch {
-                    for stmt in else_stmts {
-                        if let RuntimeValue::Return(val) = self.execute_statement(stmt)? {
-                            return Ok(RuntimeValue::Return(val));
-                        }
-                    }
+                    self.execute_scoped_block(else_stmts)
+                } else {
+                    Ok(RuntimeValue::Value)
                 }
+            }
 
-                Ok(RuntimeValue::Value)
+            Statement::IfLetStmt {
+                pattern,
+                value,
+                then_branch,
+                else_branch,
+                ..
+            } => {
+                let value = self.evaluate_expression(value)?;
+
+                // `khaali` never matches, so `agar ye n = optional` unwraps it
+                let mut bindings = Vec::new();
+                if value != LiteralValue::Khaali && match_pattern(&pattern, &value, &mut bindings) {
+                    // Names bound by the pattern are only visible in the then branch
+                    self.push_scope();
+                    for (name, var_type, bound) in bindings {
+                        self.environment.define_variable(name, var_type, bound);
+                    }
+                    let result = self.execute_block(then_branch);
+                    self.pop_scope();
+                    result
+                } else if let Some(else_stmts) = else_branch {
+                    self.execute_scoped_block(else_stmts)
+                } else {
+                    Ok(RuntimeValue::Value)
+                }
             }
 
-            Statement::ReturnStmt { value } => {
+            Statement::ReturnStmt { value, .. } => {
                 let return_value = if let Some(expr) = value {
                     self.evaluate_expression(expr)?
                 } else {
-                    LiteralValue::Int(0) // Default return value
+                    LiteralValue::Unit
                 };
                 Ok(RuntimeValue::Return(return_value))
             }
 
-            Statement::WhileStmt { condition, body } => {
+            Statement::BreakStmt { .. } => Ok(RuntimeValue::Break),
+
+            Statement::ContinueStmt { .. } => Ok(RuntimeValue::Continue),
+
+            Statement::WhileStmt {
+                condition, body, ..
+            } => {
                 loop {
                     let condition_value = self.evaluate_expression(condition.clone())?;
                     if !condition_value.is_truthy() {
                         break;
                     }
 
-                    for stmt in body.clone() {
-                        if let RuntimeValue::Return(val) = self.execute_statement(stmt)? {
-                            return Ok(RuntimeValue::Return(val));
-                        }
+                    match self.execute_scoped_block(body.clone())? {
+                        RuntimeValue::Return(val) => return Ok(RuntimeValue::Return(val)),
+                        RuntimeValue::Break => break,
+                        RuntimeValue::Continue | RuntimeValue::Value => {}
                     }
                 }
                 Ok(RuntimeValue::Value)
             }
 
+            Statement::MatchStmt { subject, arms, .. } => {
+                let value = self.evaluate_expression(subject)?;
+
+                for arm in arms {
+                    let mut bindings = Vec::new();
+                    if !match_pattern(&arm.pattern, &value, &mut bindings) {
+                        continue;
+                    }
+
+                    // Names bound by the pattern are visible to the guard and body
+                    self.push_scope();
+                    for (name, var_type, bound) in bindings {
+                        self.environment.define_variable(name, var_type, bound);
+                    }
+                    let guard_holds = match arm.guard {
+                        Some(guard) => self
+                            .evaluate_expression(guard)
+                            .map(|condition| condition.is_truthy()),
+                        None => Ok(true),
+                    };
+                    let result = match guard_holds {
+                        Ok(true) => self.execute_block(arm.body).map(Some),
+                        Ok(false) => Ok(None),
+                        Err(err) => Err(err),
+                    };
+                    self.pop_scope();
+
+                    if let Some(control) = result? {
+                        return Ok(control);
+                    }
+                }
+
+                Ok(RuntimeValue::Value)
+            }
+
             Statement::ForStmt {
                 variable,
+                value_variable,
+                variable_type,
+                value_type,
                 iterable,
                 body,
+                ..
             } => {
-                let iterable_value = self.evaluate_expression(iterable)?;
-
-                if let LiteralValue::Array(arr) = iterable_value {
-                    for element in arr {
-                        // Create new scope for loop variable
-                        let parent_env = self.environment.clone();
-                        let mut new_env = Environment::with_parent(parent_env);
-                        new_env.define_variable(variable.clone(), element);
-                        let old_env = std::mem::replace(&mut self.environment, new_env);
-
-                        for stmt in body.clone() {
-                            if let RuntimeValue::Return(val) = self.execute_statement(stmt)? {
-                                self.environment = old_env;
-                                return Ok(RuntimeValue::Return(val));
-                            }
-                        }
+                // Arrays yield (index, element) pairs when two names are
+                // given and plain elements otherwise; maps yield their entries
+                let items: Vec<(LiteralValue, Option<LiteralValue>)> = match self
+                    .evaluate_expression(iterable)?
+                {
+                    LiteralValue::Array(arr) if value_variable.is_some() => arr
+                        .into_iter()
+                        .enumerate()
+                        .map(|(index, element)| (LiteralValue::Int(index as i64), Some(element)))
+                        .collect(),
+                    LiteralValue::Array(arr) => {
+                        arr.into_iter().map(|element| (element, None)).collect()
+                    }
+                    LiteralValue::Map(entries) => entries
+                        .into_iter()
+                        .map(|(key, value)| (key, Some(value)))
+                        .collect(),
+                    _ => {
+                        return Err(PaneerError::type_error(
+                            "Can only iterate over arrays and maps",
+                        ));
+                    }
+                };
 
-                        self.environment = old_env;
+                for (first, second) in items {
+                    // Create new scope for loop variable
+                    self.push_scope();
+                    let first_type = variable_type
+                        .get()
+                        .cloned()
+                        .unwrap_or_else(|| first.get_type());
+                    self.environment
+                        .define_variable(variable.clone(), first_type, first);
+                    if let (Some(value_variable), Some(second)) = (&value_variable, second) {
+                        let second_type = value_type
+                            .get()
+                            .cloned()
+                            .unwrap_or_else(|| second.get_type());
+                        self.environment.define_variable(
+                            value_variable.clone(),
+                            second_type,
+                            second,
+                        );
+                    }
+                    let result = self.execute_block(body.clone());
+                    self.pop_scope();
+
+                    match result? {
+                        RuntimeValue::Return(val) => return Ok(RuntimeValue::Return(val)),
+                        RuntimeValue::Break => break,
+                        RuntimeValue::Continue | RuntimeValue::Value => {}
                     }
-                } else {
-                    return Err(anyhow!("Can only iterate over arrays"));
                 }
 
                 Ok(RuntimeValue::Value)
             }
+
+            Statement::RangeForStmt {
+                variable,
+                start,
+                end,
+                step,
+                body,
+                ..
+            } => {
+                let start = self.evaluate_range_bound(start, "start")?;
+                let end = self.evaluate_range_bound(end, "end")?;
+                let step = match step {
+                    Some(step) => self.evaluate_range_bound(step, "step")?,
+                    None => 1,
+                };
+
+                if step == 0 {
+                    return Err(PaneerError::runtime("Range step cannot be zero"));
+                }
+
+                // Count lazily instead of materialising the range as an array
+                let mut current = start;
+                while (step > 0 && current <= end) || (step < 0 && current >= end) {
+                    self.push_scope();
+                    self.environment.define_variable(
+                        variable.clone(),
+                        Type::Int,
+                        LiteralValue::Int(current),
+                    );
+                    let result = self.execute_block(body.clone());
+                    self.pop_scope();
+
+                    match result? {
+                        RuntimeValue::Return(val) => return Ok(RuntimeValue::Return(val)),
+                        RuntimeValue::Break => break,
+                        RuntimeValue::Continue | RuntimeValue::Value => {}
+                    }
+
+                    current = match current.checked_add(step) {
+                        Some(next) => next,
+                        None => break,
+                    };
+                }
+
+                Ok(RuntimeValue::Value)
+            }
+        }
+    }
+
+    /// Executes statements in order, stopping early on return, break or continue
+    fn execute_block(&mut self, statements: Vec<Statement>) -> Result<RuntimeValue> {
+        for stmt in statements {
+            match self.execute_statement(stmt)? {
+                RuntimeValue::Value => {}
+                control => return Ok(control),
+            }
+        }
+        Ok(RuntimeValue::Value)
+    }
+
+    /// Removes the innermost scopes until only `depth` remain, returning them
+    /// innermost first so `reattach_scopes` can put them back
+    fn detach_scopes(&mut self, depth: usize) -> Vec<Environment> {
+        let mut detached = Vec::new();
+        while self.environment.depth() > depth {
+            let Some(parent) = self.environment.parent.take() else {
+                break;
+            };
+            detached.push(std::mem::replace(&mut self.environment, *parent));
+        }
+        detached
+    }
+
+    /// Restores scopes removed by `detach_scopes`
+    fn reattach_scopes(&mut self, mut detached: Vec<Environment>) {
+        while let Some(mut scope) = detached.pop() {
+            let parent = std::mem::replace(&mut self.environment, Environment::new());
+            scope.parent = Some(Box::new(parent));
+            self.environment = scope;
+        }
+    }
+
+    /// Executes statements in a new scope of their own, so names declared in
+    /// an `agar` or `jabtak` body end with it
+    fn execute_scoped_block(&mut self, statements: Vec<Statement>) -> Result<RuntimeValue> {
+        self.push_scope();
+        let result = self.execute_block(statements);
+        self.pop_scope();
+        result
+    }
+
+    /// Evaluates one of the `se`/`tak`/`kadam` expressions of a range loop
+    fn evaluate_range_bound(&mut self, expression: Expression, part: &str) -> Result<i64> {
+        match self.evaluate_expression(expression)? {
+            LiteralValue::Int(value) => Ok(value),
+            other => Err(PaneerError::type_error(format!(
+                "Type mismatch: range {} must be int, got {}",
+                part,
+                other.get_type()
+            ))),
+        }
+    }
+
+    /// Enters a new scope whose parent is the current environment
+    ///
+    /// The current environment is moved rather than cloned, so assignments made
+    /// inside the new scope write through to the scope that defines the variable.
+    fn push_scope(&mut self) {
+        let parent = std::mem::replace(&mut self.environment, Environment::new());
+        self.environment = Environment::with_parent(parent);
+    }
+
+    /// Leaves the current scope and restores its parent environment
+    fn pop_scope(&mut self) {
+        if let Some(parent) = self.environment.parent.take() {
+            self.environment = *parent;
+        }
+    }
+
+    /// Resolves an assignment target into the variable name and the chain of
+    /// array indices and struct fields leading to the part being written
+    fn resolve_place(&mut self, target: Expression) -> Result<(String, Vec<PlaceStep>)> {
+        match target {
+            Expression::Variable { name, .. } => Ok((name, Vec::new())),
+            Expression::FieldAccess { object, field, .. } => {
+                let (name, mut steps) = self.resolve_place(*object)?;
+                steps.push(PlaceStep::Field(field));
+                Ok((name, steps))
+            }
+            Expression::ArrayAccess { array, index, .. } => {
+                let (name, mut steps) = self.resolve_place(*array)?;
+                steps.push(PlaceStep::Index(self.evaluate_expression(*index)?));
+                Ok((name, steps))
+            }
+            _ => Err(PaneerError::runtime("Invalid assignment target")),
+        }
+    }
+
+    /// Reads the current value of a variable or one of its elements or fields
+    fn read_place(&self, name: &str, steps: &[PlaceStep]) -> Result<LiteralValue> {
+        let mut value = self
+            .environment
+            .get_variable(name)
+            .ok_or_else(|| PaneerError::undefined(NameKind::Variable, name))?;
+
+        for step in steps {
+            value = match (step, value) {
+                (PlaceStep::Index(index), value) => get_index(value, index)?,
+                (PlaceStep::Field(field), value) => get_field(value, field)?,
+            };
+        }
+
+        Ok(value)
+    }
+
+    /// Writes a value into a variable or one of its array elements or fields
+    ///
+    /// The value is checked against the variable's declared type (or the
+    /// element or field type for nested writes) before anything is modified.
+    fn assign_place(&mut self, name: &str, steps: &[PlaceStep], value: LiteralValue) -> Result<()> {
+        let variable = self
+            .environment
+            .get_variable_mut(name)
+            .ok_or_else(|| PaneerError::undefined(NameKind::Variable, name))?;
+
+        let mut expected = &variable.var_type;
+        let mut slot = &mut variable.value;
+
+        for (position, step) in steps.iter().enumerate() {
+            match (step, expected, slot) {
+                (
+                    PlaceStep::Index(LiteralValue::Int(idx)),
+                    Type::Array(inner),
+                    LiteralValue::Array(arr),
+                ) => {
+                    let idx = *idx;
+                    if idx < 0 || idx as usize >= arr.len() {
+                        return Err(PaneerError::index_out_of_bounds(idx));
+                    }
+                    expected = inner;
+                    slot = &mut arr[idx as usize];
+                }
+                (PlaceStep::Index(index), Type::Array(_), LiteralValue::Array(_)) => {
+                    return Err(PaneerError::type_error(format!(
+                        "Invalid array access: index must be int, got {}",
+                        index.get_type()
+                    )));
+                }
+                (
+                    PlaceStep::Index(key),
+                    Type::Map(key_type, value_type),
+                    LiteralValue::Map(entries),
+                ) => {
+                    if !key.fits(key_type) {
+                        return Err(PaneerError::type_error(format!(
+                            "Map key type mismatch: expected {}, got {}",
+                            key_type,
+                            key.get_type()
+                        )));
+                    }
+                    expected = value_type;
+                    match entries.iter().position(|(existing, _)| existing == key) {
+                        Some(found) => slot = &mut entries[found].1,
+                        // Assigning to a missing key inserts it at the end
+                        None if position == steps.len() - 1 => {
+                            if !value.fits(expected) {
+                                return Err(PaneerError::type_error(format!(
+                                    "Type mismatch: expected {}, got {}",
+                                    expected,
+                                    value.get_type()
+                                )));
+                            }
+                            entries.push((key.clone(), value));
+                            return Ok(());
+                        }
+                        None => return Err(PaneerError::key_not_found(format_value(key))),
+                    }
+                }
+                (PlaceStep::Field(field), _, LiteralValue::Struct { name, fields }) => {
+                    let field_type = self
+                        .structs
+                        .get(name.as_str())
+                        .and_then(|decl| decl.iter().find(|(f, _)| f == field))
+                        .map(|(_, field_type)| field_type)
+                        .ok_or_else(|| {
+                            PaneerError::type_error(format!("{} has no field {}", name, field))
+                        })?;
+                    expected = field_type;
+                    slot = fields
+                        .iter_mut()
+                        .find(|(f, _)| f == field)
+                        .map(|(_, value)| value)
+                        .ok_or_else(|| {
+                            PaneerError::type_error(format!("{} has no field {}", name, field))
+                        })?;
+                }
+                (PlaceStep::Field(field), _, other) => {
+                    return Err(PaneerError::type_error(format!(
+                        "Cannot access field {} on {}",
+                        field,
+                        other.get_type()
+                    )));
+                }
+                _ => {
+                    return Err(PaneerError::type_error(format!(
+                        "Invalid array access: {} is not an array",
+                        name
+                    )));
+                }
+            }
         }
+
+        if !value.fits(expected) {
+            return Err(PaneerError::type_error(format!(
+                "Type mismatch: expected {}, got {}",
+                expected,
+                value.get_type()
+            )));
+        }
+
+        *slot = value;
+        Ok(())
     }
 
+    /// Evaluates an expression to a value, locating any error at the innermost failing node
     fn evaluate_expression(&mut self, expression: Expression) -> Result<LiteralValue> {
+        let span = expression.span();
+        self.evaluate_expression_inner(expression)
+            .map_err(|err| err.or_at(span))
+    }
+
+    fn evaluate_expression_inner(&mut self, expression: Expression) -> Result<LiteralValue> {
         match expression {
-            Expression::Literal { value } => Ok(value),
+            Expression::Literal { value, .. } => Ok(value),
 
-            Expression::Variable { name } => self
+            Expression::Variable { name, .. } => self
                 .environment
                 .get_variable(&name)
-                .ok_or_else(|| anyhow!("Undefined variable: {}", name)),
+                .ok_or_else(|| PaneerError::undefined(NameKind::Variable, name)),
 
             Expression::Binary {
                 left,
                 operator,
                 right,
+                ..
             } => {
                 let left_val = self.evaluate_expression(*left)?;
+
+                // Logical operators short-circuit, so the right side is only
+                // evaluated when it can change the result
+                match operator {
+                    BinaryOperator::And if !left_val.is_truthy() => {
+                        return Ok(LiteralValue::Bool(false));
+                    }
+                    BinaryOperator::Or if left_val.is_truthy() => {
+                        return Ok(LiteralValue::Bool(true));
+                    }
+                    BinaryOperator::And | BinaryOperator::Or => {
+                        let right_val = self.evaluate_expression(*right)?;
+                        return Ok(LiteralValue::Bool(right_val.is_truthy()));
+                    }
+                    BinaryOperator::Coalesce if left_val != LiteralValue::Khaali => {
+                        return Ok(left_val);
+                    }
+                    BinaryOperator::Coalesce => return self.evaluate_expression(*right),
+                    _ => {}
+                }
+
                 let right_val = self.evaluate_expression(*right)?;
                 self.apply_binary_operator(operator, left_val, right_val)
             }
 
-            Expression::Unary { operator, operand } => {
+            Expression::Unary {
+                operator, operand, ..
+            } => {
                 let operand_val = self.evaluate_expression(*operand)?;
                 self.apply_unary_operator(operator, operand_val)
             }
 
-            Expression::Call { callee, arguments } => {
-                if let Expression::Variable { name } = *callee {
+            Expression::Call {
+                callee, arguments, ..
+            } => {
+                if let Expression::Variable { name, .. } = *callee {
                     let function = self
                         .environment
                         .get_function(&name)
-                        .ok_or_else(|| anyhow!("Undefined function: {}", name))?;
+                        .ok_or_else(|| PaneerError::undefined(NameKind::Function, &name))?;
+                    let defining_depth = self.environment.function_depth(&name).unwrap_or(1);
 
                     if arguments.len() != function.params.len() {
-                        return Err(anyhow!(
-                            "Function {} expects {} arguments, got {}",
+                        return Err(PaneerError::arity(
                             name,
                             function.params.len(),
-                            arguments.len()
+                            arguments.len(),
                         ));
                     }
 
-                    // Create new environment for function execution
-                    let mut func_env = Environment::with_parent(self.environment.clone());
+                    // Evaluate arguments in the caller's scope
+                    let mut arg_values = Vec::new();
+                    for (argument, (param_name, param_type)) in
+                        arguments.into_iter().zip(function.params.iter())
+                    {
+                        let argument_span = argument.span();
+                        let arg_value = self.evaluate_expression(argument)?;
 
-                    // Bind parameters
-                    for (i, (param_name, param_type)) in function.params.iter().enumerate() {
-                        let arg_value = self.evaluate_expression(arguments[i].clone())?;
-
-                        if arg_value.get_type() != *param_type {
-                            return Err(anyhow!(
+                        if !arg_value.fits(param_type) {
+                            return Err(PaneerError::type_error(format!(
                                 "Argument type mismatch for parameter {}: expected {}, got {}",
                                 param_name,
                                 param_type,
                                 arg_value.get_type()
-                            ));
+                            ))
+                            .at(argument_span));
                         }
 
-                        func_env.define_variable(param_name.clone(), arg_value);
+                        arg_values.push(arg_value);
+                    }
+
+                    // The body sees the scope the function was declared in, not
+                    // the caller's locals, matching how the type checker
+                    // resolves names
+                    let caller_scopes = self.detach_scopes(defining_depth);
+                    self.push_scope();
+                    for ((param_name, param_type), arg_value) in
+                        function.params.into_iter().zip(arg_values)
+                    {
+                        self.environment
+                            .define_variable(param_name, param_type, arg_value);
                     }
 
                     // Execute function body
-                    let old_env = std::mem::replace(&mut self.environment, func_env);
+                    let result = self.execute_block(function.body);
+                    self.pop_scope();
+                    self.reattach_scopes(caller_scopes);
 
-                    let mut return_value = LiteralValue::Int(0);
-                    for stmt in function.body {
-                        if let RuntimeValue::Return(val) = self.execute_statement(stmt)? {
-                            return_value = val;
-                            break;
+                    let return_value = match result? {
+                        RuntimeValue::Return(val) => val,
+                        // `ruko`/`aage badho` outside a loop are rejected by the parser
+                        RuntimeValue::Value | RuntimeValue::Break | RuntimeValue::Continue => {
+                            LiteralValue::Unit
                         }
-                    }
-
-                    self.environment = old_env;
+                    };
 
-                    // Type check return value
-                    if return_value.get_type() != function.return_type {
-                        return Err(anyhow!(
+                    // Type check return value, naming the element that is wrong
+                    // when a tuple comes back
+                    if let (LiteralValue::Tuple(values), Type::Tuple(types)) =
+                        (&return_value, &function.return_type)
+                        && values.len() == types.len()
+                        && let Some((i, (value, expected))) = values
+                            .iter()
+                            .zip(types)
+                            .enumerate()
+                            .find(|(_, (value, expected))| !value.fits(expected))
+                    {
+                        return Err(PaneerError::type_error(format!(
+                            "Return type mismatch in tuple element {}: expected {}, got {}",
+                            i,
+                            expected,
+                            value.get_type()
+                        )));
+                    }
+                    if !return_value.fits(&function.return_type) {
+                        return Err(PaneerError::type_error(format!(
                             "Return type mismatch: expected {}, got {}",
                             function.return_type,
                             return_value.get_type()
-                        ));
+                        )));
                     }
 
                     Ok(return_value)
                 } else {
-                    Err(anyhow!("Invalid function call"))
+                    Err(PaneerError::runtime("Invalid function call"))
                 }
             }
 
@@ -346,51 +907,58 @@ impl Interpreter {
                 object,
                 method,
                 arguments,
+                ..
             } => {
-                let object_name = if let Expression::Variable { name } = object.as_ref() {
+                let object_name = if let Expression::Variable { name, .. } = object.as_ref() {
                     name.clone()
                 } else {
                     "unknown".to_string()
                 };
 
-                if let Expression::Variable { name } = *object
+                if let Expression::Variable { name, .. } = object.as_ref()
                     && name == "paneer"
                     && method == "bol"
                 {
                     // Built-in print function
                     if arguments.len() != 1 {
-                        return Err(anyhow!("paneer.bol() expects exactly 1 argument"));
+                        return Err(PaneerError::arity("paneer.bol", 1, arguments.len()));
                     }
 
                     let value = self.evaluate_expression(arguments[0].clone())?;
-                    // Convert value to string for printing
-                    let output = match value {
-                        LiteralValue::Int(i) => i.to_string(),
-                        LiteralValue::Float(f) => f.to_string(),
-                        LiteralValue::Bool(b) => b.to_string(),
-                        LiteralValue::String(s) => s,
-                        LiteralValue::Array(arr) => {
-                            let elements: Vec<String> = arr
-                                .iter()
-                                .map(|v| match v {
-                                    LiteralValue::Int(i) => i.to_string(),
-                                    LiteralValue::Float(f) => f.to_string(),
-                                    LiteralValue::Bool(b) => b.to_string(),
-                                    LiteralValue::String(s) => s.clone(),
-                                    LiteralValue::Array(_) => "[nested array]".to_string(),
-                                })
-                                .collect();
-                            format!("[{}]", elements.join(", "))
-                        }
-                    };
-                    println!("{}", output);
-                    return Ok(LiteralValue::Int(0));
+                    println!("{}", format_value(&value));
+                    return Ok(LiteralValue::Unit);
+                }
+
+                // `remove` changes the map, so it is written back to where it came from
+                if method == "remove"
+                    && matches!(
+                        *object,
+                        Expression::Variable { .. }
+                            | Expression::ArrayAccess { .. }
+                            | Expression::FieldAccess { .. }
+                    )
+                {
+                    let (name, steps) = self.resolve_place(*object)?;
+                    let mut map = self.read_place(&name, &steps)?;
+                    let argument_values = arguments
+                        .into_iter()
+                        .map(|argument| self.evaluate_expression(argument))
+                        .collect::<Result<Vec<_>>>()?;
+                    let removed =
+                        call_map_method(&mut map, &object_name, &method, argument_values)?;
+                    self.assign_place(&name, &steps, map)?;
+                    return Ok(removed);
                 }
 
-                Err(anyhow!("Unknown method: {}.{}", object_name, method))
+                let mut value = self.evaluate_expression(*object)?;
+                let argument_values = arguments
+                    .into_iter()
+                    .map(|argument| self.evaluate_expression(argument))
+                    .collect::<Result<Vec<_>>>()?;
+                call_map_method(&mut value, &object_name, &method, argument_values)
             }
 
-            Expression::ArrayLiteral { elements } => {
+            Expression::ArrayLiteral { elements, .. } => {
                 let mut array_values = Vec::new();
                 for element in elements {
                     array_values.push(self.evaluate_expression(element)?);
@@ -398,22 +966,177 @@ impl Interpreter {
                 Ok(LiteralValue::Array(array_values))
             }
 
-            Expression::ArrayAccess { array, index } => {
+            Expression::TupleLiteral { elements, .. } => {
+                let mut values = Vec::new();
+                for element in elements {
+                    values.push(self.evaluate_expression(element)?);
+                }
+                Ok(LiteralValue::Tuple(values))
+            }
+
+            Expression::TupleAccess { tuple, index, .. } => {
+                let value = self.evaluate_expression(*tuple)?;
+                get_element(value, index)
+            }
+
+            Expression::MapLiteral { entries, .. } => {
+                let mut map: Vec<(LiteralValue, LiteralValue)> = Vec::new();
+                for (key, value) in entries {
+                    let key = self.evaluate_expression(key)?;
+                    let value = self.evaluate_expression(value)?;
+                    // A repeated key keeps its first position and its last value
+                    match map.iter_mut().find(|(existing, _)| *existing == key) {
+                        Some(entry) => entry.1 = value,
+                        None => map.push((key, value)),
+                    }
+                }
+                Ok(LiteralValue::Map(map))
+            }
+
+            Expression::Conditional {
+                condition,
+                then_branch,
+                else_branch,
+                ..
+            } => {
+                if self.evaluate_expression(*condition)?.is_truthy() {
+                    self.evaluate_expression(*then_branch)
+                } else {
+                    self.evaluate_expression(*else_branch)
+                }
+            }
+
+            Expression::InterpolatedString { parts, .. } => {
+                let mut result = String::new();
+                for part in parts {
+                    match part {
+                        StringPart::Text(text) => result.push_str(&text),
+                        StringPart::Expr(expression) => {
+                            let value = self.evaluate_expression(expression)?;
+                            result.push_str(&format_value(&value));
+                        }
+                    }
+                }
+                Ok(LiteralValue::String(result))
+            }
+
+            Expression::ArrayAccess { array, index, .. } => {
                 let array_value = self.evaluate_expression(*array)?;
                 let index_value = self.evaluate_expression(*index)?;
+                get_index(array_value, &index_value)
+            }
 
-                if let (LiteralValue::Array(arr), LiteralValue::Int(idx)) =
-                    (array_value, index_value)
-                {
-                    if idx < 0 || idx as usize >= arr.len() {
-                        return Err(anyhow!("Array index out of bounds: {}", idx));
+            Expression::StructLiteral { name, fields, .. } => {
+                let declared =
+                    self.structs.get(&name).cloned().ok_or_else(|| {
+                        PaneerError::type_error(format!("Unknown type: {}", name))
+                    })?;
+
+                // Evaluate in source order, then store in declaration order
+                let mut values = Vec::new();
+                for (field, expression) in fields {
+                    let span = expression.span();
+                    let Some((_, field_type)) = declared.iter().find(|(f, _)| *f == field) else {
+                        return Err(PaneerError::type_error(format!(
+                            "{} has no field {}",
+                            name, field
+                        ))
+                        .at(span));
+                    };
+                    if values.iter().any(|(f, _)| *f == field) {
+                        return Err(PaneerError::type_error(format!(
+                            "Field {} is given more than once",
+                            field
+                        ))
+                        .at(span));
                     }
-                    Ok(arr[idx as usize].clone())
-                } else {
-                    Err(anyhow!(
-                        "Invalid array access: array must be array type and index must be int"
-                    ))
+
+                    let value = self.evaluate_expression(expression)?;
+                    if !value.fits(field_type) {
+                        return Err(PaneerError::type_error(format!(
+                            "Type mismatch for field {}: expected {}, got {}",
+                            field,
+                            field_type,
+                            value.get_type()
+                        ))
+                        .at(span));
+                    }
+                    values.push((field, value));
+                }
+
+                let mut ordered = Vec::new();
+                for (field, _) in &declared {
+                    match values.iter().position(|(f, _)| f == field) {
+                        Some(pos) => ordered.push(values.swap_remove(pos)),
+                        None => {
+                            return Err(PaneerError::type_error(format!(
+                                "Missing field {} in {}",
+                                field, name
+                            )));
+                        }
+                    }
+                }
+
+                Ok(LiteralValue::Struct {
+                    name,
+                    fields: ordered,
+                })
+            }
+
+            Expression::EnumVariant {
+                enum_name,
+                variant,
+                arguments,
+                ..
+            } => {
+                let payload_types = self
+                    .enums
+                    .get(&enum_name)
+                    .ok_or_else(|| PaneerError::type_error(format!("Unknown type: {}", enum_name)))?
+                    .iter()
+                    .find(|(name, _)| *name == variant)
+                    .map(|(_, payload_types)| payload_types.clone())
+                    .ok_or_else(|| {
+                        PaneerError::type_error(format!("{} has no variant {}", enum_name, variant))
+                    })?;
+
+                if arguments.len() != payload_types.len() {
+                    return Err(PaneerError::type_error(format!(
+                        "Variant {}::{} expects {} value(s), got {}",
+                        enum_name,
+                        variant,
+                        payload_types.len(),
+                        arguments.len()
+                    )));
                 }
+
+                let mut payload = Vec::new();
+                for (argument, expected) in arguments.into_iter().zip(&payload_types) {
+                    let span = argument.span();
+                    let value = self.evaluate_expression(argument)?;
+                    if !value.fits(expected) {
+                        return Err(PaneerError::type_error(format!(
+                            "Type mismatch in {}::{} payload: expected {}, got {}",
+                            enum_name,
+                            variant,
+                            expected,
+                            value.get_type()
+                        ))
+                        .at(span));
+                    }
+                    payload.push(value);
+                }
+
+                Ok(LiteralValue::Enum {
+                    name: enum_name,
+                    variant,
+                    payload,
+                })
+            }
+
+            Expression::FieldAccess { object, field, .. } => {
+                let value = self.evaluate_expression(*object)?;
+                get_field(value, &field)
             }
         }
     }
@@ -426,9 +1149,10 @@ impl Interpreter {
     ) -> Result<LiteralValue> {
         match (operator, &left, &right) {
             // Arithmetic operations
-            (BinaryOperator::Add, LiteralValue::Int(a), LiteralValue::Int(b)) => {
-                Ok(LiteralValue::Int(a + b))
-            }
+            (BinaryOperator::Add, LiteralValue::Int(a), LiteralValue::Int(b)) => a
+                .checked_add(*b)
+                .map(LiteralValue::Int)
+                .ok_or_else(|| PaneerError::overflow(format!("Integer overflow: {} + {}", a, b))),
             (BinaryOperator::Add, LiteralValue::Float(a), LiteralValue::Float(b)) => {
                 Ok(LiteralValue::Float(a + b))
             }
@@ -436,80 +1160,115 @@ impl Interpreter {
                 Ok(LiteralValue::String(format!("{}{}", a, b)))
             }
             // String concatenation with automatic type conversion
-            (BinaryOperator::Add, LiteralValue::String(a), right) => {
-                let right_str = match right {
-                    LiteralValue::Int(i) => i.to_string(),
-                    LiteralValue::Float(f) => f.to_string(),
-                    LiteralValue::Bool(b) => b.to_string(),
-                    LiteralValue::String(s) => s.clone(),
-                    LiteralValue::Array(arr) => {
-                        let elements: Vec<String> = arr
-                            .iter()
-                            .map(|v| match v {
-                                LiteralValue::Int(i) => i.to_string(),
-                                LiteralValue::Float(f) => f.to_string(),
-                                LiteralValue::Bool(b) => b.to_string(),
-                                LiteralValue::String(s) => s.clone(),
-                                LiteralValue::Array(_) => "[nested]".to_string(),
-                            })
-                            .collect();
-                        format!("[{}]", elements.join(", "))
-                    }
-                };
-                Ok(LiteralValue::String(format!("{}{}", a, right_str)))
-            }
+            (BinaryOperator::Add, LiteralValue::String(a), right) => Ok(LiteralValue::String(
+                format!("{}{}", a, format_value(right)),
+            )),
             (BinaryOperator::Add, left, LiteralValue::String(b)) => {
-                let left_str = match left {
-                    LiteralValue::Int(i) => i.to_string(),
-                    LiteralValue::Float(f) => f.to_string(),
-                    LiteralValue::Bool(b_val) => b_val.to_string(),
-                    LiteralValue::String(s) => s.clone(),
-                    LiteralValue::Array(arr) => {
-                        let elements: Vec<String> = arr
-                            .iter()
-                            .map(|v| match v {
-                                LiteralValue::Int(i) => i.to_string(),
-                                LiteralValue::Float(f) => f.to_string(),
-                                LiteralValue::Bool(b) => b.to_string(),
-                                LiteralValue::String(s) => s.clone(),
-                                LiteralValue::Array(_) => "[nested]".to_string(),
-                            })
-                            .collect();
-                        format!("[{}]", elements.join(", "))
-                    }
-                };
-                Ok(LiteralValue::String(format!("{}{}", left_str, b)))
+                Ok(LiteralValue::String(format!("{}{}", format_value(left), b)))
             }
 
-            (BinaryOperator::Subtract, LiteralValue::Int(a), LiteralValue::Int(b)) => {
-                Ok(LiteralValue::Int(a - b))
-            }
+            (BinaryOperator::Subtract, LiteralValue::Int(a), LiteralValue::Int(b)) => a
+                .checked_sub(*b)
+                .map(LiteralValue::Int)
+                .ok_or_else(|| PaneerError::overflow(format!("Integer overflow: {} - {}", a, b))),
             (BinaryOperator::Subtract, LiteralValue::Float(a), LiteralValue::Float(b)) => {
                 Ok(LiteralValue::Float(a - b))
             }
 
-            (BinaryOperator::Multiply, LiteralValue::Int(a), LiteralValue::Int(b)) => {
-                Ok(LiteralValue::Int(a * b))
-            }
+            (BinaryOperator::Multiply, LiteralValue::Int(a), LiteralValue::Int(b)) => a
+                .checked_mul(*b)
+                .map(LiteralValue::Int)
+                .ok_or_else(|| PaneerError::overflow(format!("Integer overflow: {} * {}", a, b))),
             (BinaryOperator::Multiply, LiteralValue::Float(a), LiteralValue::Float(b)) => {
                 Ok(LiteralValue::Float(a * b))
             }
 
             (BinaryOperator::Divide, LiteralValue::Int(a), LiteralValue::Int(b)) => {
                 if *b == 0 {
-                    Err(anyhow!("Division by zero"))
+                    Err(PaneerError::division_by_zero("Division by zero"))
                 } else {
-                    Ok(LiteralValue::Int(a / b))
+                    a.checked_div(*b).map(LiteralValue::Int).ok_or_else(|| {
+                        PaneerError::overflow(format!("Integer overflow: {} / {}", a, b))
+                    })
                 }
             }
             (BinaryOperator::Divide, LiteralValue::Float(a), LiteralValue::Float(b)) => {
                 if *b == 0.0 {
-                    Err(anyhow!("Division by zero"))
+                    Err(PaneerError::division_by_zero("Division by zero"))
                 } else {
                     Ok(LiteralValue::Float(a / b))
                 }
             }
 
+            (BinaryOperator::Modulo, LiteralValue::Int(a), LiteralValue::Int(b)) => {
+                if *b == 0 {
+                    Err(PaneerError::division_by_zero("Modulo by zero"))
+                } else {
+                    a.checked_rem(*b).map(LiteralValue::Int).ok_or_else(|| {
+                        PaneerError::overflow(format!("Integer overflow: {} % {}", a, b))
+                    })
+                }
+            }
+            (BinaryOperator::Modulo, LiteralValue::Float(a), LiteralValue::Float(b)) => {
+                if *b == 0.0 {
+                    Err(PaneerError::division_by_zero("Modulo by zero"))
+                } else {
+                    Ok(LiteralValue::Float(a % b))
+                }
+            }
+
+            (BinaryOperator::Power, LiteralValue::Int(a), LiteralValue::Int(b)) => {
+                if *b < 0 {
+                    return Err(PaneerError::invalid_operand(
+                        operator,
+                        format!("Negative exponent: {}", b),
+                    ));
+                }
+                u32::try_from(*b)
+                    .ok()
+                    .and_then(|exp| a.checked_pow(exp))
+                    .map(LiteralValue::Int)
+                    .ok_or_else(|| {
+                        PaneerError::overflow(format!("Integer overflow: {} ** {}", a, b))
+                    })
+            }
+            (BinaryOperator::Power, LiteralValue::Float(a), LiteralValue::Float(b)) => {
+                Ok(LiteralValue::Float(a.powf(*b)))
+            }
+
+            // Bitwise operations
+            (BinaryOperator::BitAnd, LiteralValue::Int(a), LiteralValue::Int(b)) => {
+                Ok(LiteralValue::Int(a & b))
+            }
+            (BinaryOperator::BitOr, LiteralValue::Int(a), LiteralValue::Int(b)) => {
+                Ok(LiteralValue::Int(a | b))
+            }
+            (BinaryOperator::BitXor, LiteralValue::Int(a), LiteralValue::Int(b)) => {
+                Ok(LiteralValue::Int(a ^ b))
+            }
+            (
+                BinaryOperator::ShiftLeft | BinaryOperator::ShiftRight,
+                LiteralValue::Int(a),
+                LiteralValue::Int(b),
+            ) => {
+                if *b < 0 {
+                    return Err(PaneerError::invalid_operand(
+                        operator,
+                        format!("Negative shift amount: {}", b),
+                    ));
+                }
+                let shifted = u32::try_from(*b).ok().and_then(|amount| {
+                    if matches!(operator, BinaryOperator::ShiftLeft) {
+                        a.checked_shl(amount)
+                    } else {
+                        a.checked_shr(amount)
+                    }
+                });
+                shifted.map(LiteralValue::Int).ok_or_else(|| {
+                    PaneerError::invalid_operand(operator, format!("Shift amount too large: {}", b))
+                })
+            }
+
             // Comparison operations
             (BinaryOperator::Equal, _, _) => Ok(LiteralValue::Bool(left == right)),
             (BinaryOperator::NotEqual, _, _) => Ok(LiteralValue::Bool(left != right)),
@@ -542,12 +1301,10 @@ impl Interpreter {
                 Ok(LiteralValue::Bool(a <= b))
             }
 
-            _ => Err(anyhow!(
+            _ => Err(PaneerError::type_error(format!(
                 "Invalid binary operation: {} {:?} {}",
-                left,
-                operator,
-                right
-            )),
+                left, operator, right
+            ))),
         }
     }
 
@@ -557,10 +1314,631 @@ impl Interpreter {
         operand: LiteralValue,
     ) -> Result<LiteralValue> {
         match (operator, operand) {
-            (UnaryOperator::Minus, LiteralValue::Int(value)) => Ok(LiteralValue::Int(-value)),
+            (UnaryOperator::Minus, LiteralValue::Int(value)) => value
+                .checked_neg()
+                .map(LiteralValue::Int)
+                .ok_or_else(|| PaneerError::overflow(format!("Integer overflow: -({})", value))),
             (UnaryOperator::Minus, LiteralValue::Float(value)) => Ok(LiteralValue::Float(-value)),
             (UnaryOperator::Not, value) => Ok(LiteralValue::Bool(!value.is_truthy())),
-            _ => Err(anyhow!("Invalid unary operation")),
+            _ => Err(PaneerError::type_error("Invalid unary operation")),
+        }
+    }
+}
+
+/// Tests `value` against `pattern`, collecting the names it binds along with
+/// the types they are declared with
+fn match_pattern(
+    pattern: &Pattern,
+    value: &LiteralValue,
+    bindings: &mut Vec<(String, Type, LiteralValue)>,
+) -> bool {
+    match (pattern, value) {
+        (Pattern::Wildcard { .. }, _) => true,
+        (
+            Pattern::Binding {
+                name, binding_type, ..
+            },
+            _,
+        ) => {
+            let var_type = binding_type
+                .get()
+                .cloned()
+                .unwrap_or_else(|| value.get_type());
+            bindings.push((name.clone(), var_type, value.clone()));
+            true
         }
+        (
+            Pattern::Literal {
+                value: expected, ..
+            },
+            _,
+        ) => expected == value,
+        (
+            Pattern::Range {
+                start: LiteralValue::Int(start),
+                end: LiteralValue::Int(end),
+                ..
+            },
+            LiteralValue::Int(value),
+        ) => (start..=end).contains(&value),
+        (
+            Pattern::Range {
+                start: LiteralValue::Float(start),
+                end: LiteralValue::Float(end),
+                ..
+            },
+            LiteralValue::Float(value),
+        ) => (start..=end).contains(&value),
+        (
+            Pattern::Array {
+                elements,
+                rest,
+                rest_type,
+                ..
+            },
+            LiteralValue::Array(items),
+        ) => {
+            let length_fits = match rest {
+                Some(_) => items.len() >= elements.len(),
+                None => items.len() == elements.len(),
+            };
+            if !length_fits
+                || !elements
+                    .iter()
+                    .zip(items)
+                    .all(|(element, item)| match_pattern(element, item, bindings))
+            {
+                return false;
+            }
+            if let Some(Some(name)) = rest {
+                let remaining = LiteralValue::Array(items[elements.len()..].to_vec());
+                let var_type = rest_type
+                    .get()
+                    .cloned()
+                    .unwrap_or_else(|| remaining.get_type());
+                bindings.push((name.clone(), var_type, remaining));
+            }
+            true
+        }
+        (
+            Pattern::Variant {
+                enum_name,
+                variant,
+                payload: patterns,
+                ..
+            },
+            LiteralValue::Enum {
+                name,
+                variant: actual,
+                payload,
+            },
+        ) => {
+            enum_name == name
+                && variant == actual
+                && patterns.len() == payload.len()
+                && patterns
+                    .iter()
+                    .zip(payload)
+                    .all(|(pattern, item)| match_pattern(pattern, item, bindings))
+        }
+        _ => false,
+    }
+}
+
+/// Converts a value to the text `paneer.bol` prints for it
+fn format_value(value: &LiteralValue) -> String {
+    match value {
+        LiteralValue::Int(i) => i.to_string(),
+        LiteralValue::Float(f) => format_float(*f),
+        LiteralValue::Bool(b) => b.to_string(),
+        LiteralValue::String(s) => s.clone(),
+        LiteralValue::Array(arr) => {
+            let elements: Vec<String> = arr
+                .iter()
+                .map(|v| match v {
+                    LiteralValue::Array(_) => "[nested array]".to_string(),
+                    other => format_value(other),
+                })
+                .collect();
+            format!("[{}]", elements.join(", "))
+        }
+        LiteralValue::Map(entries) => {
+            let entries: Vec<String> = entries
+                .iter()
+                .map(|(key, value)| format!("{}: {}", format_value(key), format_value(value)))
+                .collect();
+            format!("{{{}}}", entries.join(", "))
+        }
+        LiteralValue::Tuple(elements) => {
+            let elements: Vec<String> = elements.iter().map(format_value).collect();
+            format!("({})", elements.join(", "))
+        }
+        LiteralValue::Khaali => "khaali".to_string(),
+        LiteralValue::Unit => "kuch_nahi".to_string(),
+        LiteralValue::Struct { name, fields } => {
+            let fields: Vec<String> = fields
+                .iter()
+                .map(|(field, value)| format!("{}: {}", field, format_value(value)))
+                .collect();
+            format!("{} {{ {} }}", name, fields.join(", "))
+        }
+        LiteralValue::Enum {
+            name,
+            variant,
+            payload,
+        } => {
+            if payload.is_empty() {
+                format!("{}::{}", name, variant)
+            } else {
+                let values: Vec<String> = payload.iter().map(format_value).collect();
+                format!("{}::{}({})", name, variant, values.join(", "))
+            }
+        }
+    }
+}
+
+/// Reads one element out of a tuple
+fn get_element(value: LiteralValue, index: usize) -> Result<LiteralValue> {
+    match value {
+        LiteralValue::Tuple(mut elements) => {
+            if index >= elements.len() {
+                return Err(PaneerError::type_error(format!(
+                    "Tuple index {} out of range for {}",
+                    index,
+                    LiteralValue::Tuple(elements).get_type()
+                )));
+            }
+            Ok(elements.swap_remove(index))
+        }
+        other => Err(PaneerError::type_error(format!(
+            "Cannot read element {} of {}, it is not a tuple",
+            index,
+            other.get_type()
+        ))),
+    }
+}
+
+/// Reads an array element or map entry out of a value
+fn get_index(value: LiteralValue, index: &LiteralValue) -> Result<LiteralValue> {
+    match (value, index) {
+        (LiteralValue::Array(mut arr), LiteralValue::Int(idx)) => {
+            let idx = *idx;
+            if idx < 0 || idx as usize >= arr.len() {
+                return Err(PaneerError::index_out_of_bounds(idx));
+            }
+            Ok(arr.swap_remove(idx as usize))
+        }
+        (LiteralValue::Map(entries), key) => entries
+            .into_iter()
+            .find(|(existing, _)| existing == key)
+            .map(|(_, value)| value)
+            .ok_or_else(|| PaneerError::key_not_found(format_value(key))),
+        (LiteralValue::Array(_), other) => Err(PaneerError::type_error(format!(
+            "Invalid array access: index must be int, got {}",
+            other.get_type()
+        ))),
+        (other, _) => Err(PaneerError::type_error(format!(
+            "Invalid array access: {} is not an array or map",
+            other.get_type()
+        ))),
+    }
+}
+
+/// Runs one of the built-in map methods `has`, `remove`, `keys`, `values`
+/// and `len` on `value`, changing it in place for `remove`
+fn call_map_method(
+    value: &mut LiteralValue,
+    object_name: &str,
+    method: &str,
+    arguments: Vec<LiteralValue>,
+) -> Result<LiteralValue> {
+    let unknown = || PaneerError::runtime(format!("Unknown method: {}.{}", object_name, method));
+    let LiteralValue::Map(entries) = value else {
+        return Err(unknown());
+    };
+
+    let expected = match method {
+        "has" | "remove" => 1,
+        "keys" | "values" | "len" => 0,
+        _ => return Err(unknown()),
+    };
+    if arguments.len() != expected {
+        return Err(PaneerError::arity(method, expected, arguments.len()));
+    }
+
+    Ok(match method {
+        "has" => LiteralValue::Bool(entries.iter().any(|(key, _)| *key == arguments[0])),
+        "remove" => {
+            let before = entries.len();
+            entries.retain(|(key, _)| *key != arguments[0]);
+            LiteralValue::Bool(entries.len() != before)
+        }
+        "keys" => LiteralValue::Array(entries.iter().map(|(key, _)| key.clone()).collect()),
+        "values" => LiteralValue::Array(entries.iter().map(|(_, value)| value.clone()).collect()),
+        _ => LiteralValue::Int(entries.len() as i64),
+    })
+}
+
+/// Reads a field out of a struct value
+fn get_field(value: LiteralValue, field: &str) -> Result<LiteralValue> {
+    match value {
+        LiteralValue::Struct { name, fields } => fields
+            .into_iter()
+            .find(|(f, _)| f == field)
+            .map(|(_, value)| value)
+            .ok_or_else(|| PaneerError::type_error(format!("{} has no field {}", name, field))),
+        other => Err(PaneerError::type_error(format!(
+            "Cannot access field {} on {}",
+            field,
+            other.get_type()
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lexer::Lexer;
+    use crate::parser::Parser;
+    use crate::typeck::TypeChecker;
+
+    /// Lexes, parses, type checks and runs `source`, returning the
+    /// interpreter so tests can read the global variables it left behind
+    fn run(source: &str) -> std::result::Result<Interpreter, Vec<PaneerError>> {
+        let lexer = Lexer::new(source).map_err(|err| vec![err])?;
+        let (program, parse_errors) = Parser::new(lexer).parse();
+        if !parse_errors.is_empty() {
+            return Err(parse_errors);
+        }
+        TypeChecker::new().check(&program)?;
+        let mut interpreter = Interpreter::new();
+        interpreter.interpret(program).map_err(|err| vec![err])?;
+        Ok(interpreter)
+    }
+
+    fn global(interpreter: &Interpreter, name: &str) -> LiteralValue {
+        interpreter
+            .environment
+            .get_variable(name)
+            .unwrap_or_else(|| panic!("{} is not defined", name))
+    }
+
+    #[test]
+    fn if_and_while_bodies_have_their_own_scope() {
+        let interpreter = run(r#"
+            ye x: int = 1;
+            agar true { ye x: string = "then"; } varna { ye x: string = "else"; }
+            ye i: int = 0;
+            jabtak i < 2 { ye x: string = "loop"; i += 1; }
+            ye y: int = x + 1;
+        "#)
+        .unwrap();
+        assert_eq!(global(&interpreter, "y"), LiteralValue::Int(2));
+    }
+
+    #[test]
+    fn functions_see_their_defining_scope_not_the_caller() {
+        let interpreter = run(r#"
+            ye x: int = 1;
+            func f() int { return x; }
+            func h() int { ye x: string = "s"; return f(); }
+            ye result: int = h();
+
+            ye counter: int = 0;
+            func bump() { counter += 1; }
+            agar true { ye counter: string = "shadow"; bump(); }
+            bump();
+        "#)
+        .unwrap();
+        assert_eq!(global(&interpreter, "result"), LiteralValue::Int(1));
+        assert_eq!(global(&interpreter, "counter"), LiteralValue::Int(2));
+    }
+
+    #[test]
+    fn pattern_bindings_take_the_type_the_checker_gave_them() {
+        let interpreter = run(r#"
+            ye p: int? = 5;
+            ye cleared: bool = false;
+            jab p {
+                n => {
+                    n = khaali;
+                    cleared = n == khaali;
+                }
+            }
+
+            ye items: array<int?> = [1, 2];
+            ye rest_cleared: bool = false;
+            jab items {
+                [first, ..rest] => {
+                    rest = [khaali];
+                    rest_cleared = rest[0] == khaali;
+                }
+                _ => {}
+            }
+        "#)
+        .unwrap();
+        assert_eq!(global(&interpreter, "cleared"), LiteralValue::Bool(true));
+        assert_eq!(
+            global(&interpreter, "rest_cleared"),
+            LiteralValue::Bool(true)
+        );
+    }
+
+    #[test]
+    fn loop_variables_take_the_type_the_checker_gave_them() {
+        let interpreter = run(r#"
+            ye a: array<int?> = [1, 2];
+            ye cleared: int = 0;
+            har x mein a {
+                x = khaali;
+                cleared += 1;
+            }
+
+            ye m: map<string, int?> = {"a": 1};
+            har key, value mein m {
+                value = khaali;
+                cleared += 1;
+            }
+
+            func empty() array<string> { wapas kar []; }
+            ye nested: array<array<string>> = [empty()];
+            har inner mein nested {
+                inner = ["chai"];
+                cleared += 1;
+            }
+        "#)
+        .unwrap();
+        assert_eq!(global(&interpreter, "cleared"), LiteralValue::Int(4));
+    }
+
+    #[test]
+    fn integer_overflow_is_an_error_not_a_panic() {
+        let overflows = [
+            "ye x: int = 9223372036854775807; x += 1;",
+            "ye x: int = 9223372036854775807; x++;",
+            "ye x: int = -9223372036854775807; ye y: int = x - 2;",
+            "ye x: int = 9223372036854775807; ye y: int = x * 2;",
+            "ye x: int = -9223372036854775807 - 1; ye y: int = x / -1;",
+            "ye x: int = -9223372036854775807 - 1; ye y: int = -x;",
+        ];
+        for source in overflows {
+            let errors = run(source)
+                .err()
+                .unwrap_or_else(|| panic!("{} ran", source));
+            assert!(
+                matches!(errors[..], [PaneerError::Overflow { .. }]),
+                "{}: {:?}",
+                source,
+                errors
+            );
+        }
+    }
+
+    #[test]
+    fn range_loops_count_inclusively_in_either_direction() {
+        let interpreter = run(r#"
+            ye up: string = "";
+            har i se 1 tak 3 { up = up + i; }
+
+            ye stepped: string = "";
+            har i se 0 tak 10 kadam 4 { stepped = stepped + i; }
+
+            ye down: string = "";
+            har i se 3 tak 1 kadam -1 { down = down + i; }
+
+            ye odd: string = "";
+            har i se 10 tak 1 kadam -3 { odd = odd + i + ","; }
+
+            ye never: int = 0;
+            har i se 1 tak 3 kadam -1 { never += 1; }
+        "#)
+        .unwrap();
+        let text = |name| global(&interpreter, name);
+        assert_eq!(text("up"), LiteralValue::String("123".to_string()));
+        assert_eq!(text("stepped"), LiteralValue::String("048".to_string()));
+        assert_eq!(text("down"), LiteralValue::String("321".to_string()));
+        assert_eq!(text("odd"), LiteralValue::String("10,7,4,1,".to_string()));
+        assert_eq!(text("never"), LiteralValue::Int(0));
+    }
+
+    #[test]
+    fn a_zero_range_step_is_a_runtime_error() {
+        let errors = run("har i se 1 tak 3 kadam 0 { paneer.bol(i); }")
+            .err()
+            .unwrap();
+        assert!(
+            matches!(errors[..], [PaneerError::Runtime { .. }]),
+            "{:?}",
+            errors
+        );
+    }
+
+    #[test]
+    fn programs_can_be_written_in_devanagari() {
+        let interpreter = run(r#"
+            ये नाम: स्ट्रिंग = "अमित";
+            ये उम्र: इंट = 25;
+            ये संदेश: स्ट्रिंग = "";
+            अगर उम्र >= 18 और सच {
+                संदेश = f"{नाम} vote de sakta hai";
+            } वरना {
+                संदेश = "abhi nahi";
+            }
+            फंक दुगना(n इंट) इंट { वापस कर n * 2; }
+            ये कुल: इंट = 0;
+            हर i से 1 तक 3 { कुल += दुगना(i); }
+        "#)
+        .unwrap();
+        assert_eq!(
+            global(&interpreter, "संदेश"),
+            LiteralValue::String("अमित vote de sakta hai".to_string())
+        );
+        assert_eq!(global(&interpreter, "कुल"), LiteralValue::Int(12));
+    }
+
+    #[test]
+    fn struct_fields_can_be_read_and_assigned() {
+        let interpreter = run(r#"
+            dhancha Point { x: int, y: int }
+            dhancha Order { item: string, quantity: int, at: Point }
+
+            ye p: Point = Point { y: 2, x: 1 };
+            ye order: Order = Order { item: "Paneer Tikka", quantity: 2, at: p };
+            order.quantity += 1;
+            order.at.x = 10;
+            p.y = 5;
+
+            ye x: int = order.at.x;
+            ye quantity: int = order.quantity;
+            ye copied_y: int = order.at.y;
+        "#)
+        .unwrap();
+        assert_eq!(
+            global(&interpreter, "p"),
+            LiteralValue::Struct {
+                name: "Point".to_string(),
+                fields: vec![
+                    ("x".to_string(), LiteralValue::Int(1)),
+                    ("y".to_string(), LiteralValue::Int(5)),
+                ],
+            }
+        );
+        assert_eq!(global(&interpreter, "x"), LiteralValue::Int(10));
+        assert_eq!(global(&interpreter, "quantity"), LiteralValue::Int(3));
+        // `order.at` holds its own copy of `p`
+        assert_eq!(global(&interpreter, "copied_y"), LiteralValue::Int(2));
+    }
+
+    #[test]
+    fn enum_payloads_are_bound_by_jab_and_agar_ye() {
+        let interpreter = run(r#"
+            prakar Status {
+                Pending,
+                Paid(float),
+                Failed(string, int),
+            }
+
+            func describe(s Status) string {
+                jab s {
+                    Status::Pending => wapas kar "pending";
+                    Status::Paid(amount) agar amount > 100.0 => wapas kar f"big {amount}";
+                    Status::Paid(amount) => wapas kar f"paid {amount}";
+                    Status::Failed(reason, code) => wapas kar f"{reason} ({code})";
+                }
+            }
+
+            ye pending: string = describe(Status::Pending);
+            ye small: string = describe(Status::Paid(10.5));
+            ye big: string = describe(Status::Paid(250.0));
+            ye failed: string = describe(Status::Failed("declined", 51));
+
+            ye refund: float = 0.0;
+            agar ye Status::Paid(amount) = Status::Paid(4.5) {
+                refund = amount;
+            }
+            ye missed: bool = true;
+            agar ye Status::Paid(amount) = Status::Pending {
+                missed = false;
+            }
+        "#)
+        .unwrap();
+        let text = |name| global(&interpreter, name);
+        assert_eq!(text("pending"), LiteralValue::String("pending".to_string()));
+        assert_eq!(text("small"), LiteralValue::String("paid 10.5".to_string()));
+        assert_eq!(text("big"), LiteralValue::String("big 250".to_string()));
+        assert_eq!(
+            text("failed"),
+            LiteralValue::String("declined (51)".to_string())
+        );
+        assert_eq!(text("refund"), LiteralValue::Float(4.5));
+        assert_eq!(text("missed"), LiteralValue::Bool(true));
+    }
+
+    #[test]
+    fn maps_keep_insertion_order() {
+        let interpreter = run(r#"
+            ye prices: map<string, int> = {"chai": 20, "samosa": 15};
+            prices["vada pav"] = 25;
+            prices["chai"] += 5;
+            ye removed: bool = prices.remove("samosa");
+            ye removed_again: bool = prices.remove("samosa");
+            prices["samosa"] = 18;
+
+            ye order: string = "";
+            har item, price mein prices {
+                order = order + f"{item}={price};";
+            }
+            ye keys: string = "";
+            har item mein prices {
+                keys = keys + item + ",";
+            }
+            ye has_chai: bool = prices.has("chai");
+            ye count: int = prices.len();
+            ye values: array<int> = prices.values();
+        "#)
+        .unwrap();
+        let value = |name| global(&interpreter, name);
+        assert_eq!(value("removed"), LiteralValue::Bool(true));
+        assert_eq!(value("removed_again"), LiteralValue::Bool(false));
+        assert_eq!(
+            value("order"),
+            LiteralValue::String("chai=25;vada pav=25;samosa=18;".to_string())
+        );
+        assert_eq!(
+            value("keys"),
+            LiteralValue::String("chai,vada pav,samosa,".to_string())
+        );
+        assert_eq!(value("has_chai"), LiteralValue::Bool(true));
+        assert_eq!(value("count"), LiteralValue::Int(3));
+        assert_eq!(
+            value("values"),
+            LiteralValue::Array([25, 25, 18].map(LiteralValue::Int).to_vec())
+        );
+    }
+
+    #[test]
+    fn reading_a_missing_map_key_is_an_error() {
+        let errors = run(r#"ye m: map<string, int> = {}; ye n: int = m["chai"];"#)
+            .err()
+            .unwrap();
+        assert!(
+            matches!(errors[..], [PaneerError::KeyNotFound { .. }]),
+            "{:?}",
+            errors
+        );
+    }
+
+    #[test]
+    fn tuples_destructure_and_nest() {
+        let interpreter = run(r#"
+            func divmod(a int, b int) (int, int) {
+                wapas kar (a / b, a % b);
+            }
+            ye (q, r): (int, int) = divmod(7, 2);
+            ye (_, rest): (int, int) = divmod(9, 4);
+
+            ye t: (string, (int, bool)) = ("chai", (20, true));
+            ye price: int = t.1.0;
+            ye hot: bool = t.1.1;
+        "#)
+        .unwrap();
+        assert_eq!(global(&interpreter, "q"), LiteralValue::Int(3));
+        assert_eq!(global(&interpreter, "r"), LiteralValue::Int(1));
+        assert_eq!(global(&interpreter, "rest"), LiteralValue::Int(1));
+        assert_eq!(global(&interpreter, "price"), LiteralValue::Int(20));
+        assert_eq!(global(&interpreter, "hot"), LiteralValue::Bool(true));
+        assert!(interpreter.environment.get_variable("_").is_none());
+    }
+
+    #[test]
+    fn extreme_floats_print_in_exponent_notation() {
+        let printed = [6.02e23, -6.02e23, 1.5e-7, 1e16, 2.5, 250.0, 0.001, 0.0]
+            .map(|value| format_value(&LiteralValue::Float(value)));
+        assert_eq!(
+            printed,
+            [
+                "6.02e23", "-6.02e23", "1.5e-7", "1e16", "2.5", "250", "0.001", "0"
+            ]
+        );
     }
 }
@@ -3,7 +3,10 @@
 //! This module handles tokenization of PaneerLang source code using the logos crate.
 //! It converts raw text into a stream of tokens that can be consumed by the parser.
 
+use crate::ast::Span;
+use crate::errors::PaneerError;
 use logos::Logos;
+use std::collections::{HashMap, HashSet};
 
 /// Token types for PaneerLang lexical analysis
 ///
@@ -11,89 +14,173 @@ use logos::Logos;
 /// Supports Hindi keywords, operators, literals, and identifiers.
 #[derive(Logos, Debug, PartialEq, Clone)]
 #[logos(skip r"[ \t\n\f]+")]
+#[logos(error = LexError)]
 pub enum Token {
     // Keywords - Hindi-inspired language constructs
+    //
+    // Every keyword can also be written in Devanagari, e.g. `ये` for `ye`.
     /// Built-in object for core functions
     #[token("paneer")]
+    #[token("पनीर")]
     Paneer,
 
     #[token("bol")]
+    #[token("बोल")]
     Bol,
 
     #[token("ye")]
+    #[token("ये")]
     Ye,
 
     #[token("agar")]
+    #[token("अगर")]
     Agar,
 
     #[token("toh")]
+    #[token("तो")]
     Toh,
 
     #[token("varna")]
+    #[token("वरना")]
     Varna,
 
     #[token("func")]
+    #[token("फंक")]
     Func,
 
+    #[token("dhancha")]
+    #[token("ढांचा")]
+    Dhancha, // struct
+
+    #[token("prakar")]
+    #[token("प्रकार")]
+    Prakar, // enum
+
     #[token("return")]
+    #[token("रिटर्न")]
     Return,
 
     #[token("wapas")]
+    #[token("वापस")]
     Wapas,
 
     #[token("kar")]
+    #[token("कर")]
     Kar,
 
     #[token("jabtak")]
+    #[token("जबतक")]
     Jabtak, // while
 
+    #[token("jab")]
+    #[token("जब")]
+    Jab, // match
+
     #[token("har")]
+    #[token("हर")]
     Har, // for
 
     #[token("mein")]
+    #[token("में")]
     Mein, // in
 
     #[token("se")]
+    #[token("से")]
     Se, // from
 
     #[token("tak")]
+    #[token("तक")]
     Tak, // to
 
+    #[token("kadam")]
+    #[token("कदम")]
+    Kadam, // step
+
+    #[token("ruko")]
+    #[token("रुको")]
+    Ruko, // break
+
+    #[token("aage")]
+    #[token("आगे")]
+    Aage, // continue (with badho)
+
+    #[token("badho")]
+    // `ढ़` may be typed with a combining nukta or as the precomposed letter
+    #[token("बढ़ो")]
+    #[token("बढ़ो")]
+    Badho,
+
     // Types
     #[token("int")]
+    #[token("इंट")]
     IntType,
 
     #[token("float")]
+    #[token("फ्लोट")]
     FloatType,
 
     #[token("string")]
+    #[token("स्ट्रिंग")]
     StringType,
 
     #[token("bool")]
+    #[token("बूल")]
     BoolType,
 
     #[token("array")]
+    #[token("ऐरे")]
     ArrayType,
 
+    #[token("map")]
+    #[token("मैप")]
+    MapType,
+
+    /// Return type of a function that produces no value
+    #[token("kuch_nahi")]
+    #[token("कुछ_नहीं")]
+    KuchNahi,
+
     // Literals
-    #[regex(r"-?[0-9]+", |lex| lex.slice().parse::<i64>().ok())]
-    IntLiteral(i64),
+    //
+    // Numeric literals are unsigned; a leading `-` is parsed as unary minus.
+    // `IntLiteral` holds a `u64` so that `-9223372036854775808` can still be
+    // written, and the parser rejects anything else above `i64::MAX`.
+    /// Decimal `1_000`, hexadecimal `0xFF`, binary `0b1010` or octal `0o755`
+    #[regex(r"[0-9][0-9_]*", lex_int)]
+    #[regex(r"0[xXbBoO][0-9a-zA-Z_]*", lex_int)]
+    IntLiteral(u64),
 
-    #[regex(r"-?[0-9]+\.[0-9]+", |lex| lex.slice().parse::<f64>().ok())]
+    /// `3.14`, `1_000.5` or scientific `6.02e23`
+    #[regex(r"[0-9][0-9_]*\.[0-9][0-9_]*([eE][+-]?[0-9][0-9_]*)?", lex_float)]
+    #[regex(r"[0-9][0-9_]*[eE][+-]?[0-9][0-9_]*", lex_float)]
     FloatLiteral(f64),
 
-    #[regex(r#""([^"\\]|\\["\\nt])*""#, |lex| {
+    /// `"..."` with escapes, `r"..."` raw, or a `"""..."""` text block
+    #[regex(r#""([^"\\]|\\.)*""#, lex_string)]
+    #[regex(r#"r"[^"]*""#, |lex| {
         let s = lex.slice();
-        s[1..s.len()-1].to_string()
+        s[2..s.len()-1].to_string()
     })]
+    #[token("\"\"\"", lex_text_block)]
     StringLiteral(String),
 
+    /// `f"..."` string with `{expression}` placeholders
+    #[token("f\"", lex_format_string)]
+    FormatString(Vec<FormatPart>),
+
     #[token("true")]
+    #[token("सच")]
     True,
 
     #[token("false")]
+    #[token("झूठ")]
     False,
 
+    /// The missing value of an optional type
+    #[token("khaali")]
+    #[token("खाली")]
+    Khaali,
+
     // Operators
     #[token("+")]
     Plus,
@@ -107,6 +194,45 @@ pub enum Token {
     #[token("/")]
     Slash,
 
+    #[token("%")]
+    Percent,
+
+    #[token("**")]
+    StarStar,
+
+    #[token("&")]
+    Ampersand,
+
+    #[token("|")]
+    Pipe,
+
+    #[token("^")]
+    Caret,
+
+    #[token("<<")]
+    ShiftLeft,
+
+    #[token(">>")]
+    ShiftRight,
+
+    #[token("+=")]
+    PlusAssign,
+
+    #[token("-=")]
+    MinusAssign,
+
+    #[token("*=")]
+    StarAssign,
+
+    #[token("/=")]
+    SlashAssign,
+
+    #[token("++")]
+    PlusPlus,
+
+    #[token("--")]
+    MinusMinus,
+
     #[token("==")]
     Equal,
 
@@ -128,6 +254,22 @@ pub enum Token {
     #[token("<=")]
     LessEqual,
 
+    #[token("&&")]
+    #[token("aur")]
+    #[token("और")]
+    And,
+
+    #[token("||")]
+    #[token("ya")]
+    #[token("या")]
+    Or,
+
+    #[token("?")]
+    Question,
+
+    #[token("??")]
+    QuestionQuestion,
+
     // Delimiters
     #[token("(")]
     LeftParen,
@@ -147,28 +289,392 @@ pub enum Token {
     #[token(":")]
     Colon,
 
+    #[token("::")]
+    ColonColon,
+
     #[token("=")]
     Assign,
 
+    #[token("=>")]
+    FatArrow,
+
     #[token(",")]
     Comma,
 
     #[token(".")]
     Dot,
 
+    #[token("..")]
+    DotDot,
+
     #[token("[")]
     LeftBracket,
 
     #[token("]")]
     RightBracket,
 
-    // Identifier
-    #[regex(r"[a-zA-Z_][a-zA-Z0-9_]*", |lex| lex.slice().to_string())]
+    /// Identifier made of Unicode letters, digits and marks, such as `naam`
+    /// or `नाम`
+    #[regex(r"[\p{XID_Start}_]\p{XID_Continue}*", |lex| lex.slice().to_string())]
     Identifier(String),
 
     // Comments
     #[regex(r"//[^\n]*", logos::skip)]
     Comment,
+
+    /// `/* ... */`, which may contain nested block comments
+    #[token("/*", lex_block_comment)]
+    BlockComment,
+
+    /// `/// text`, documenting the declaration that follows
+    #[regex(r"///[^\n]*", |lex| {
+        let text = &lex.slice()[3..];
+        text.strip_prefix(' ').unwrap_or(text).to_string()
+    })]
+    DocComment(String),
+}
+
+/// A piece of an `f"..."` string
+#[derive(Debug, PartialEq, Clone)]
+pub enum FormatPart {
+    /// Literal text, with escapes already decoded
+    Text(String),
+    /// Source of an embedded expression and its byte offset in the file
+    Code { source: String, offset: usize },
+}
+
+/// Errors produced while tokenizing
+#[derive(Debug, Clone, PartialEq, Default)]
+pub enum LexError {
+    /// No token matches the text at this position
+    #[default]
+    UnexpectedCharacter,
+    /// A malformed literal, such as an unknown escape sequence
+    InvalidLiteral { message: String, span: Span },
+}
+
+/// Skips a block comment, keeping track of nested `/* */` pairs
+fn lex_block_comment(lex: &mut logos::Lexer<Token>) -> Result<logos::Skip, LexError> {
+    let remainder = lex.remainder();
+    let mut depth = 1;
+    let mut index = 0;
+
+    while index < remainder.len() {
+        match &remainder.as_bytes()[index..(index + 2).min(remainder.len())] {
+            b"/*" => {
+                depth += 1;
+                index += 2;
+            }
+            b"*/" => {
+                depth -= 1;
+                index += 2;
+                if depth == 0 {
+                    lex.bump(index);
+                    return Ok(logos::Skip);
+                }
+            }
+            _ => index += 1,
+        }
+    }
+
+    let opening = lex.span();
+    lex.bump(remainder.len());
+    Err(LexError::InvalidLiteral {
+        message: "Unterminated block comment: expected closing */".to_string(),
+        span: opening.into(),
+    })
+}
+
+/// Parses an integer literal in any base, ignoring `_` separators
+fn lex_int(lex: &mut logos::Lexer<Token>) -> Result<u64, LexError> {
+    let slice = lex.slice();
+    let invalid = |message: String| LexError::InvalidLiteral {
+        message,
+        span: lex.span().into(),
+    };
+
+    let (radix, name, digits) = match slice.get(..2) {
+        Some("0x" | "0X") => (16, "hexadecimal", &slice[2..]),
+        Some("0b" | "0B") => (2, "binary", &slice[2..]),
+        Some("0o" | "0O") => (8, "octal", &slice[2..]),
+        _ => (10, "decimal", slice),
+    };
+    let digits = digits.replace('_', "");
+
+    if digits.is_empty() {
+        return Err(invalid(format!(
+            "Missing digits in {} literal {}",
+            name, slice
+        )));
+    }
+    if let Some(digit) = digits.chars().find(|c| !c.is_digit(radix)) {
+        return Err(invalid(format!(
+            "Invalid digit '{}' in {} literal {}",
+            digit, name, slice
+        )));
+    }
+
+    u64::from_str_radix(&digits, radix)
+        .map_err(|_| invalid(format!("Integer literal {} is too large for int", slice)))
+}
+
+/// Parses a float literal, ignoring `_` separators
+fn lex_float(lex: &mut logos::Lexer<Token>) -> Result<f64, LexError> {
+    let slice = lex.slice();
+    match slice.replace('_', "").parse::<f64>() {
+        Ok(value) if value.is_finite() => Ok(value),
+        _ => Err(LexError::InvalidLiteral {
+            message: format!("Float literal {} is too large for float", slice),
+            span: lex.span().into(),
+        }),
+    }
+}
+
+/// Decodes a `"..."` string literal
+fn lex_string(lex: &mut logos::Lexer<Token>) -> Result<String, LexError> {
+    let slice = lex.slice();
+    decode_escapes(&slice[1..slice.len() - 1], lex.span().start + 1)
+}
+
+/// Scans a `"""..."""` text block, which may span several lines
+///
+/// A line break straight after the opening quotes is not part of the text, so
+/// the block can start on its own line.
+fn lex_text_block(lex: &mut logos::Lexer<Token>) -> Result<String, LexError> {
+    let remainder = lex.remainder();
+    let opening = lex.span();
+
+    let mut escaped = false;
+    let mut end = None;
+    for (index, ch) in remainder.char_indices() {
+        if escaped {
+            escaped = false;
+        } else if ch == '\\' {
+            escaped = true;
+        } else if remainder[index..].starts_with("\"\"\"") {
+            end = Some(index);
+            break;
+        }
+    }
+
+    let Some(end) = end else {
+        lex.bump(remainder.len());
+        return Err(LexError::InvalidLiteral {
+            message: "Unterminated text block: expected closing \"\"\"".to_string(),
+            span: opening.into(),
+        });
+    };
+    lex.bump(end + 3);
+
+    let text = &remainder[..end];
+    let body = text
+        .strip_prefix("\r\n")
+        .or_else(|| text.strip_prefix('\n'))
+        .unwrap_or(text);
+    decode_escapes(body, opening.end + (text.len() - body.len()))
+}
+
+/// Splits an `f"..."` string into literal text and embedded expression source
+///
+/// `{{` and `}}` stand for literal braces. The expressions themselves are
+/// parsed later by the parser.
+fn lex_format_string(lex: &mut logos::Lexer<Token>) -> Result<Vec<FormatPart>, LexError> {
+    let remainder = lex.remainder();
+    let opening = lex.span();
+    let body_start = opening.end;
+    let error_at = |start: usize, end: usize, message: &str| LexError::InvalidLiteral {
+        message: message.to_string(),
+        span: Span::new(body_start + start, body_start + end),
+    };
+
+    let mut parts = Vec::new();
+    let mut text = String::new();
+    let mut chunk_start = 0;
+    let mut chars = remainder.char_indices().peekable();
+
+    while let Some((index, ch)) = chars.next() {
+        match ch {
+            '"' => {
+                text.push_str(&decode_escapes(
+                    &remainder[chunk_start..index],
+                    body_start + chunk_start,
+                )?);
+                if !text.is_empty() {
+                    parts.push(FormatPart::Text(text));
+                }
+                lex.bump(index + 1);
+                return Ok(parts);
+            }
+            '\\' => {
+                // Escapes are decoded with the rest of the text chunk. The
+                // braces of a `\u{...}` escape are part of it, not an
+                // interpolation, so the whole group is skipped
+                if let Some((_, 'u')) = chars.next()
+                    && chars.peek().map(|&(_, next)| next) == Some('{')
+                {
+                    while let Some(&(_, next)) = chars.peek() {
+                        if next == '"' {
+                            break;
+                        }
+                        chars.next();
+                        if next == '}' {
+                            break;
+                        }
+                    }
+                }
+            }
+            '{' | '}' if chars.peek().map(|&(_, next)| next) == Some(ch) => {
+                text.push_str(&decode_escapes(
+                    &remainder[chunk_start..index],
+                    body_start + chunk_start,
+                )?);
+                text.push(ch);
+                chars.next();
+                chunk_start = index + 2;
+            }
+            '}' => {
+                return Err(error_at(
+                    index,
+                    index + 1,
+                    "Unmatched '}' in interpolated string, use '}}' for a literal brace",
+                ));
+            }
+            '{' => {
+                text.push_str(&decode_escapes(
+                    &remainder[chunk_start..index],
+                    body_start + chunk_start,
+                )?);
+                if !text.is_empty() {
+                    parts.push(FormatPart::Text(std::mem::take(&mut text)));
+                }
+
+                // Find the matching '}', skipping braces inside nested strings
+                let mut depth = 0;
+                let mut in_string = false;
+                let mut end = None;
+                while let Some((inner, inner_ch)) = chars.next() {
+                    match (in_string, inner_ch) {
+                        (true, '\\') => {
+                            chars.next();
+                        }
+                        (_, '"') => in_string = !in_string,
+                        (false, '{') => depth += 1,
+                        (false, '}') if depth == 0 => {
+                            end = Some(inner);
+                            break;
+                        }
+                        (false, '}') => depth -= 1,
+                        _ => {}
+                    }
+                }
+
+                let Some(end) = end else {
+                    return Err(error_at(
+                        index,
+                        index + 1,
+                        "Unclosed '{' in interpolated string, use '{{' for a literal brace",
+                    ));
+                };
+
+                let source = &remainder[index + 1..end];
+                if source.trim().is_empty() {
+                    return Err(error_at(
+                        index,
+                        end + 1,
+                        "Empty expression in interpolated string",
+                    ));
+                }
+
+                parts.push(FormatPart::Code {
+                    source: source.to_string(),
+                    offset: body_start + index + 1,
+                });
+                chunk_start = end + 1;
+            }
+            _ => {}
+        }
+    }
+
+    lex.bump(remainder.len());
+    Err(LexError::InvalidLiteral {
+        message: "Unterminated interpolated string".to_string(),
+        span: opening.into(),
+    })
+}
+
+/// Replaces escape sequences in the body of a string literal
+///
+/// Supports `\n`, `\t`, `\r`, `\0`, `\"`, `\\` and `\u{...}` with 1 to 6 hex
+/// digits. `offset` is the position of `text` in the source, used to point
+/// errors at the offending escape.
+fn decode_escapes(text: &str, offset: usize) -> Result<String, LexError> {
+    let mut result = String::with_capacity(text.len());
+    let mut chars = text.char_indices();
+
+    while let Some((index, ch)) = chars.next() {
+        if ch != '\\' {
+            result.push(ch);
+            continue;
+        }
+
+        let invalid = |len: usize, message: String| LexError::InvalidLiteral {
+            message,
+            span: Span::new(offset + index, offset + index + len),
+        };
+
+        match chars.next() {
+            Some((_, 'n')) => result.push('\n'),
+            Some((_, 't')) => result.push('\t'),
+            Some((_, 'r')) => result.push('\r'),
+            Some((_, '0')) => result.push('\0'),
+            Some((_, '"')) => result.push('"'),
+            Some((_, '\\')) => result.push('\\'),
+            Some((_, 'u')) => {
+                let digits = text[index + 2..]
+                    .strip_prefix('{')
+                    .and_then(|rest| rest.find('}').map(|end| &rest[..end]));
+                let Some(digits) = digits else {
+                    return Err(invalid(
+                        2,
+                        "Invalid unicode escape: expected \\u{...}".to_string(),
+                    ));
+                };
+
+                let decoded = Some(digits)
+                    .filter(|digits| {
+                        (1..=6).contains(&digits.len())
+                            && digits.chars().all(|c| c.is_ascii_hexdigit())
+                    })
+                    .and_then(|digits| u32::from_str_radix(digits, 16).ok())
+                    .and_then(char::from_u32);
+                match decoded {
+                    Some(decoded) => result.push(decoded),
+                    None => {
+                        return Err(invalid(
+                            digits.len() + 4,
+                            format!("Invalid unicode escape: \\u{{{}}}", digits),
+                        ));
+                    }
+                }
+
+                // Skip the braces and digits that were just decoded
+                for _ in 0..digits.chars().count() + 2 {
+                    chars.next();
+                }
+            }
+            Some((_, other)) => {
+                return Err(invalid(
+                    1 + other.len_utf8(),
+                    format!("Unknown escape sequence: \\{}", other),
+                ));
+            }
+            None => {
+                return Err(invalid(1, "Unfinished escape sequence".to_string()));
+            }
+        }
+    }
+
+    Ok(result)
 }
 
 /// Lexer state for tokenizing PaneerLang source code
@@ -180,6 +686,12 @@ pub struct Lexer {
     tokens: Vec<(Token, std::ops::Range<usize>)>,
     /// Current position in the token stream
     current: usize,
+    /// Length of the source, used as the position of the end of input
+    eof: usize,
+    /// Doc comment text keyed by the index of the token it documents
+    docs: HashMap<usize, String>,
+    /// Indices of the tokens that are the first on their line
+    line_starts: HashSet<usize>,
 }
 
 impl Lexer {
@@ -190,25 +702,85 @@ impl Lexer {
     ///
     /// # Returns
     /// * `Ok(Lexer)` - Successfully tokenized lexer
-    /// * `Err(String)` - Error message if tokenization fails
-    pub fn new(input: &str) -> Result<Self, String> {
+    /// * `Err(PaneerError::Lex)` - Error pointing at the offending text if tokenization fails
+    pub fn new(input: &str) -> Result<Self, PaneerError> {
+        Self::with_offset(input, 0)
+    }
+
+    /// Tokenizes a fragment of a larger source that starts at byte `offset`
+    ///
+    /// Spans are reported relative to the whole source, which lets the parser
+    /// lex the expressions embedded in `f"..."` strings.
+    pub fn with_offset(input: &str, offset: usize) -> Result<Self, PaneerError> {
+        let shift = |span: std::ops::Range<usize>| span.start + offset..span.end + offset;
         let mut tokens = Vec::new();
+        let mut docs = HashMap::new();
+        let mut doc_lines: Vec<String> = Vec::new();
+        let mut line_starts = HashSet::new();
+        let mut previous_end = 0;
         let mut lex = Token::lexer(input);
 
         while let Some(token) = lex.next() {
             match token {
-                Ok(token) => tokens.push((token, lex.span())),
-                Err(_) => {
-                    return Err(format!(
-                        "Unexpected character at position {}: '{}'",
-                        lex.span().start,
+                // Doc comments stay out of the token stream and are attached to
+                // whichever token follows them
+                Ok(Token::DocComment(line)) => doc_lines.push(line),
+                // `t.0.1` lexes `0.1` as a float, but after a '.' it is two
+                // tuple indices, so split it into `0`, `.` and `1`
+                Ok(Token::FloatLiteral(_))
+                    if matches!(tokens.last(), Some((Token::Dot, _)))
+                        && let Some((first, second)) = lex.slice().split_once('.')
+                        && let (Ok(first), Ok(second)) = (first.parse(), second.parse()) =>
+                {
+                    let span = shift(lex.span());
+                    let dot = span.start + lex.slice().find('.').unwrap_or_default();
+                    tokens.push((Token::IntLiteral(first), span.start..dot));
+                    tokens.push((Token::Dot, dot..dot + 1));
+                    tokens.push((Token::IntLiteral(second), dot + 1..span.end));
+                    previous_end = lex.span().end;
+                }
+                Ok(token) => {
+                    if !doc_lines.is_empty() {
+                        docs.insert(tokens.len(), doc_lines.join("\n"));
+                        doc_lines.clear();
+                    }
+                    if tokens.is_empty() || input[previous_end..lex.span().start].contains('\n') {
+                        line_starts.insert(tokens.len());
+                    }
+                    previous_end = lex.span().end;
+                    tokens.push((token, shift(lex.span())));
+                }
+                Err(LexError::UnexpectedCharacter) => {
+                    return Err(PaneerError::lex(format!(
+                        "Unexpected character: '{}'",
                         &input[lex.span()]
-                    ));
+                    ))
+                    .at(shift(lex.span()).into()));
+                }
+                Err(LexError::InvalidLiteral { message, span }) => {
+                    return Err(PaneerError::lex(message)
+                        .at(Span::new(span.start + offset, span.end + offset)));
                 }
             }
         }
 
-        Ok(Lexer { tokens, current: 0 })
+        Ok(Lexer {
+            tokens,
+            current: 0,
+            eof: offset + input.len(),
+            docs,
+            line_starts,
+        })
+    }
+
+    /// Whether the current token is the first one on its line
+    pub fn at_line_start(&self) -> bool {
+        self.line_starts.contains(&self.current)
+    }
+
+    /// Returns the doc comment written just before the current token
+    pub fn doc_comment(&self) -> Option<String> {
+        self.docs.get(&self.current).cloned()
     }
 
     /// Peeks at the current token without consuming it
@@ -220,6 +792,41 @@ impl Lexer {
         self.tokens.get(self.current).map(|(token, _)| token)
     }
 
+    /// Returns the token `n` positions after the current one without consuming anything
+    pub fn peek_nth(&self, n: usize) -> Option<&Token> {
+        self.tokens.get(self.current + n).map(|(token, _)| token)
+    }
+
+    /// Returns the source span of the current token, or the end of input
+    pub fn peek_span(&self) -> Span {
+        self.tokens
+            .get(self.current)
+            .map_or(Span::new(self.eof, self.eof), |(_, span)| {
+                span.clone().into()
+            })
+    }
+
+    /// Returns the most recently consumed token
+    pub fn previous(&self) -> Option<&Token> {
+        self.current
+            .checked_sub(1)
+            .and_then(|index| self.tokens.get(index))
+            .map(|(token, _)| token)
+    }
+
+    /// Returns the index of the current token, used to detect whether parsing made progress
+    pub fn position(&self) -> usize {
+        self.current
+    }
+
+    /// Returns the source span of the most recently consumed token
+    pub fn previous_span(&self) -> Span {
+        self.current
+            .checked_sub(1)
+            .and_then(|index| self.tokens.get(index))
+            .map_or(Span::default(), |(_, span)| span.clone().into())
+    }
+
     /// Consumes and returns the current token, advancing to the next
     ///
     /// # Returns
@@ -235,6 +842,17 @@ impl Lexer {
         }
     }
 
+    /// Replaces the current token with the second half of a split token
+    ///
+    /// Used to treat `>>` as two `>` when closing nested generic types such as
+    /// `array<array<int>>`.
+    pub fn split_current(&mut self, token: Token) {
+        if let Some((current, span)) = self.tokens.get_mut(self.current) {
+            *current = token;
+            span.start += 1;
+        }
+    }
+
     /// Checks if the lexer has reached the end of the token stream
     ///
     /// # Returns
@@ -244,3 +862,195 @@ impl Lexer {
         self.current >= self.tokens.len()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tokens(source: &str) -> Vec<Token> {
+        let mut lexer = Lexer::new(source).unwrap();
+        let mut tokens = Vec::new();
+        while let Some(token) = lexer.advance() {
+            tokens.push(token.clone());
+        }
+        tokens
+    }
+
+    /// Returns the message of the error lexing `source` fails with and the
+    /// text its span covers
+    fn lex_error(source: &str) -> (String, &str) {
+        let Err(error) = Lexer::new(source) else {
+            panic!("{} lexed without errors", source);
+        };
+        let span = error.span().unwrap();
+        (error.to_string(), &source[span.start..span.end])
+    }
+
+    fn string(text: &str) -> Token {
+        Token::StringLiteral(text.to_string())
+    }
+
+    #[test]
+    fn string_escapes_are_decoded() {
+        assert_eq!(
+            tokens(r#""a\nb\t\r\0\"q\"\\""#),
+            [string("a\nb\t\r\0\"q\"\\")]
+        );
+        assert_eq!(tokens(r#""\u{41}\u{928}\u{1F9C0}""#), [string("Aन🧀")]);
+        assert_eq!(tokens(r#"r"C:\new\u{41}""#), [string(r"C:\new\u{41}")]);
+        assert_eq!(
+            tokens("\"\"\"\nsays \"hi\"\n\\tok\"\"\""),
+            [string("says \"hi\"\n\tok")]
+        );
+    }
+
+    #[test]
+    fn bad_escapes_point_at_the_escape() {
+        assert_eq!(
+            lex_error(r#"ye s: string = "ab\qc";"#),
+            ("Unknown escape sequence: \\q".to_string(), r"\q")
+        );
+        assert_eq!(
+            lex_error(r#""\u41""#),
+            (
+                "Invalid unicode escape: expected \\u{...}".to_string(),
+                r"\u"
+            )
+        );
+        assert_eq!(
+            lex_error(r#""\u{}""#),
+            ("Invalid unicode escape: \\u{}".to_string(), r"\u{}")
+        );
+        assert_eq!(
+            lex_error(r#""\u{D800}""#),
+            ("Invalid unicode escape: \\u{D800}".to_string(), r"\u{D800}")
+        );
+        assert_eq!(
+            lex_error(r#""\u{1234567}""#).0,
+            "Invalid unicode escape: \\u{1234567}"
+        );
+    }
+
+    #[test]
+    fn numeric_literals_in_every_base() {
+        assert_eq!(
+            tokens("0xFF 0Xff 0b1010 0o755 1_000_000 0x_dead_BEEF 007"),
+            [255, 255, 10, 493, 1_000_000, 0xDEAD_BEEF, 7].map(Token::IntLiteral)
+        );
+        assert_eq!(
+            tokens("2.5 1_000.5 6.02e23 1.5e-3 2E+2 1e3"),
+            [2.5, 1000.5, 6.02e23, 1.5e-3, 200.0, 1000.0].map(Token::FloatLiteral)
+        );
+        assert_eq!(
+            tokens("18446744073709551615"),
+            [Token::IntLiteral(u64::MAX)]
+        );
+    }
+
+    #[test]
+    fn malformed_numeric_literals_are_errors() {
+        assert_eq!(
+            lex_error("ye x: int = 0x;"),
+            ("Missing digits in hexadecimal literal 0x".to_string(), "0x")
+        );
+        assert_eq!(
+            lex_error("0b102").0,
+            "Invalid digit '2' in binary literal 0b102"
+        );
+        assert_eq!(lex_error("0o8").0, "Invalid digit '8' in octal literal 0o8");
+        assert_eq!(
+            lex_error("18446744073709551616").0,
+            "Integer literal 18446744073709551616 is too large for int"
+        );
+        assert_eq!(
+            lex_error("1e400").0,
+            "Float literal 1e400 is too large for float"
+        );
+    }
+
+    #[test]
+    fn block_comments_nest() {
+        assert_eq!(
+            tokens("ye /* outer /* inner */ still outer */ x /**/ = 1;"),
+            [
+                Token::Ye,
+                Token::Identifier("x".to_string()),
+                Token::Assign,
+                Token::IntLiteral(1),
+                Token::Semicolon,
+            ]
+        );
+        assert_eq!(
+            lex_error("x /* one /* two */"),
+            (
+                "Unterminated block comment: expected closing */".to_string(),
+                "/*"
+            )
+        );
+    }
+
+    #[test]
+    fn doc_comments_attach_to_the_next_token() {
+        let mut lexer = Lexer::new("/// Adds\n///two\nfunc // note\nx /// trailing\n").unwrap();
+        assert_eq!(lexer.doc_comment(), Some("Adds\ntwo".to_string()));
+        lexer.advance();
+        assert_eq!(lexer.doc_comment(), None);
+        lexer.advance();
+        assert!(lexer.is_at_end());
+    }
+
+    #[test]
+    fn devanagari_keywords_lex_like_their_latin_spelling() {
+        let latin = "paneer bol ye agar toh varna func dhancha prakar return wapas kar \
+                     jabtak jab har mein se tak kadam ruko aage badho int float string \
+                     bool array map kuch_nahi true false khaali aur ya";
+        let devanagari = "पनीर बोल ये अगर तो वरना फंक ढांचा प्रकार रिटर्न वापस कर \
+                          जबतक जब हर में से तक कदम रुको आगे बढ़ो इंट फ्लोट स्ट्रिंग \
+                          बूल ऐरे मैप कुछ_नहीं सच झूठ खाली और या";
+        assert_eq!(tokens(devanagari), tokens(latin));
+        // `बढ़ो` written with a combining nukta instead of the precomposed `ढ़`
+        assert_eq!(tokens("\u{92C}\u{922}\u{93C}\u{94B}"), [Token::Badho]);
+    }
+
+    #[test]
+    fn identifiers_may_use_devanagari_letters_and_marks() {
+        assert_eq!(
+            tokens("नाम उम्र_2 जबतकि"),
+            ["नाम", "उम्र_2", "जबतकि"].map(|name| Token::Identifier(name.to_string()))
+        );
+    }
+
+    #[test]
+    fn chained_tuple_indices_are_not_floats() {
+        let t = || Token::Identifier("t".to_string());
+        assert_eq!(
+            tokens("t.1.0 x = 1.0"),
+            [
+                t(),
+                Token::Dot,
+                Token::IntLiteral(1),
+                Token::Dot,
+                Token::IntLiteral(0),
+                Token::Identifier("x".to_string()),
+                Token::Assign,
+                Token::FloatLiteral(1.0),
+            ]
+        );
+    }
+
+    #[test]
+    fn unicode_escapes_in_format_strings_are_not_interpolations() {
+        let mut lexer = Lexer::new(r#"f"A=\u{41} n={n}!""#).unwrap();
+        assert_eq!(
+            lexer.advance(),
+            Some(&Token::FormatString(vec![
+                FormatPart::Text("A=A n=".to_string()),
+                FormatPart::Code {
+                    source: "n".to_string(),
+                    offset: 14,
+                },
+                FormatPart::Text("!".to_string()),
+            ]))
+        );
+    }
+}
@@ -4,20 +4,26 @@ mod errors;
 mod interpreter;
 mod lexer;
 mod parser;
+mod typeck;
 mod ui;
 mod utils;
 
 use clap::{Arg, Command};
 use colored::*;
+use std::collections::HashMap;
 use std::fs;
 use std::io::{self, Write};
 
+use ast::{Program, Statement, Type};
 use debug::DebugInfo;
+use errors::diagnostics::format_warning;
 use errors::funny_errors::FunnyErrorGenerator;
 use errors::hinglish_errors::HinglishErrorGenerator;
+use errors::{PaneerError, Warning};
 use interpreter::Interpreter;
 use lexer::Lexer;
 use parser::Parser;
+use typeck::TypeChecker;
 use ui::*;
 use utils::colors::PaneerColors;
 
@@ -80,7 +86,7 @@ fn run_file(file_path: &str, debug: bool) {
         Err(err) => {
             let error_gen = HinglishErrorGenerator::new();
             let hinglish_error = error_gen.format_hinglish_error(
-                &format!("Could not read file: {}", err),
+                &PaneerError::io(format!("Could not read file: {}", err)),
                 Some(file_path),
                 None,
             );
@@ -96,12 +102,12 @@ fn run_file(file_path: &str, debug: bool) {
 
     print_execution_start();
 
-    if let Err(err) = execute(&source, debug) {
+    if let Err(errors) = execute(&source, debug) {
         println!("{}", PaneerColors::separator(&"─".repeat(60)));
 
         let error_gen = HinglishErrorGenerator::new();
         let hinglish_error =
-            error_gen.format_hinglish_error(&err.to_string(), Some(file_path), None);
+            error_gen.format_hinglish_errors(&errors, Some(file_path), Some(&source));
         eprintln!("{}", hinglish_error);
         std::process::exit(1);
     }
@@ -117,6 +123,10 @@ fn start_repl() {
     print_repl_banner();
 
     let mut interpreter = Interpreter::new();
+    let mut checker = TypeChecker::new();
+    let mut docs = HashMap::new();
+    // `///` lines typed so far, prepended to the next declaration
+    let mut pending_docs = String::new();
 
     loop {
         print!("{} ", "paneer>".blue().bold());
@@ -141,21 +151,24 @@ fn start_repl() {
                     continue;
                 }
 
+                if let Some(name) = input.strip_prefix(":doc") {
+                    let name = name.trim();
+                    print_doc(name, docs.get(name));
+                    continue;
+                }
+
+                if input.starts_with("///") {
+                    pending_docs.push_str(input);
+                    pending_docs.push('\n');
+                    continue;
+                }
+                let input = std::mem::take(&mut pending_docs) + input;
+
                 // For REPL, execute single statements with error handling
-                if let Err(err) = execute_repl(&mut interpreter, input) {
+                if let Err(errors) = execute_repl(&mut interpreter, &mut checker, &mut docs, &input)
+                {
                     let error_gen = FunnyErrorGenerator::new();
-                    let error_type = if err.to_string().contains("type") {
-                        "type"
-                    } else if err.to_string().contains("Undefined") {
-                        "undefined"
-                    } else if err.to_string().contains("Expected") {
-                        "syntax"
-                    } else {
-                        "general"
-                    };
-
-                    let funny_error =
-                        error_gen.format_error(error_type, &err.to_string(), None, None);
+                    let funny_error = error_gen.format_errors(&errors, None, Some(&input));
                     eprintln!("{}", funny_error);
                 }
             }
@@ -175,24 +188,35 @@ fn start_repl() {
 ///
 /// # Returns
 /// * `Ok(())` if execution succeeds
-/// * `Err(Box<dyn std::error::Error>)` if any phase fails
-fn execute(source: &str, debug: bool) -> Result<(), Box<dyn std::error::Error>> {
+/// * `Err(Vec<PaneerError>)` with every error reported by the phase that failed
+fn execute(source: &str, debug: bool) -> Result<(), Vec<PaneerError>> {
     let debug_info = DebugInfo::new(debug);
     let start_time = std::time::Instant::now();
 
     // Phase 1: Lexical Analysis
     debug_info.print_phase("Lexical Analysis");
     debug_info.print_lexer_info(source);
-    let lexer = Lexer::new(source)?;
+    let lexer = Lexer::new(source).map_err(|err| vec![err])?;
 
     // Phase 2: Parsing
     debug_info.print_phase("Syntax Analysis");
     let mut parser = Parser::new(lexer);
-    let program = parser.parse()?;
-    debug_info.print_parser_info(true, Some(program.statements.len()));
+    let (program, parse_errors) = parser.parse();
+    debug_info.print_parser_info(parse_errors.is_empty(), Some(program.statements.len()));
     debug_info.print_ast_structure(&program);
+    if !parse_errors.is_empty() {
+        return Err(parse_errors);
+    }
 
-    // Phase 3: Interpretation
+    // Phase 3: Type Checking
+    debug_info.print_phase("Type Checking");
+    let mut checker = TypeChecker::new();
+    let typeck_result = checker.check(&program);
+    debug_info.print_typeck_info(typeck_result.as_ref().err().map_or(0, Vec::len));
+    typeck_result?;
+    print_warnings(&checker.take_warnings(), source);
+
+    // Phase 4: Interpretation
     debug_info.print_phase("Code Execution");
     let mut interpreter = Interpreter::new();
     let result = interpreter.interpret(program);
@@ -208,7 +232,7 @@ fn execute(source: &str, debug: bool) -> Result<(), Box<dyn std::error::Error>>
         Err(e) => {
             debug_info.print_interpreter_info(false);
             debug_info.print_execution_summary(false, Some(duration));
-            Err(e.into())
+            Err(vec![e])
         }
     }
 }
@@ -217,27 +241,187 @@ fn execute(source: &str, debug: bool) -> Result<(), Box<dyn std::error::Error>>
 ///
 /// # Arguments
 /// * `interpreter` - Mutable reference to the interpreter instance
+/// * `checker` - Type checker that remembers declarations from earlier inputs
+/// * `docs` - Signatures and doc comments of declarations, for `:doc`
 /// * `input` - The user input to execute
 ///
 /// # Returns
 /// * `Ok(())` if execution succeeds
-/// * `Err(Box<dyn std::error::Error>)` if parsing or execution fails
+/// * `Err(Vec<PaneerError>)` if parsing, type checking or execution fails
 fn execute_repl(
     interpreter: &mut Interpreter,
+    checker: &mut TypeChecker,
+    docs: &mut HashMap<String, DocEntry>,
     input: &str,
-) -> Result<(), Box<dyn std::error::Error>> {
-    // Add semicolon if not present for single expressions
-    let input = if !input.ends_with(';') && !input.contains('{') {
-        format!("{};", input)
-    } else {
-        input.to_string()
+) -> Result<(), Vec<PaneerError>> {
+    // Add semicolon if not present for single statements. A closing brace
+    // may end a block, which takes no semicolon, or a map or struct literal,
+    // which does, so keep whichever version parses
+    let trimmed = input.trim_end();
+    let with_semicolon = format!("{};", trimmed);
+    let input = match trimmed.chars().last() {
+        Some(';') | None => trimmed.to_string(),
+        Some('}') if parses(trimmed) || !parses(&with_semicolon) => trimmed.to_string(),
+        _ => with_semicolon,
     };
 
-    let lexer = Lexer::new(&input)?;
+    let lexer = Lexer::new(&input).map_err(|err| vec![err])?;
     let mut parser = Parser::new(lexer);
-    let program = parser.parse()?;
+    let (program, parse_errors) = parser.parse();
+    if !parse_errors.is_empty() {
+        return Err(parse_errors);
+    }
 
-    interpreter.interpret(program)?;
+    // Declarations only stay visible to later inputs when this one succeeds
+    let saved_checker = checker.clone();
+    let typeck_result = checker.check(&program);
+    print_warnings(&checker.take_warnings(), &input);
+    if let Err(errors) = typeck_result {
+        *checker = saved_checker;
+        return Err(errors);
+    }
+    let declarations = collect_docs(&program);
+    if let Err(err) = interpreter.interpret(program) {
+        *checker = saved_checker;
+        return Err(vec![err]);
+    }
+    docs.extend(declarations);
 
     Ok(())
 }
+
+/// Whether `input` lexes and parses without errors
+fn parses(input: &str) -> bool {
+    Lexer::new(input).is_ok_and(|lexer| Parser::new(lexer).parse().1.is_empty())
+}
+
+/// Prints type checker warnings to stderr
+fn print_warnings(warnings: &[Warning], source: &str) {
+    for warning in warnings {
+        eprintln!("{}", format_warning(warning, source));
+    }
+}
+
+/// Builds the `:doc` entries for the top-level declarations of a program
+fn collect_docs(program: &Program) -> Vec<(String, DocEntry)> {
+    program
+        .statements
+        .iter()
+        .filter_map(|statement| match statement {
+            Statement::VarDecl {
+                name,
+                type_annotation,
+                doc,
+                ..
+            } => Some((
+                name.clone(),
+                DocEntry {
+                    signature: format!("ye {}: {}", name, type_annotation),
+                    doc: doc.clone(),
+                },
+            )),
+            Statement::FuncDecl {
+                name,
+                params,
+                return_type,
+                doc,
+                ..
+            } => {
+                let params: Vec<String> = params
+                    .iter()
+                    .map(|(param, param_type)| format!("{} {}", param, param_type))
+                    .collect();
+                Some((
+                    name.clone(),
+                    DocEntry {
+                        signature: format!("func {}({}) {}", name, params.join(", "), return_type),
+                        doc: doc.clone(),
+                    },
+                ))
+            }
+            Statement::StructDecl {
+                name, fields, doc, ..
+            } => {
+                let fields: Vec<String> = fields
+                    .iter()
+                    .map(|(field, field_type)| format!("{}: {}", field, field_type))
+                    .collect();
+                Some((
+                    name.clone(),
+                    DocEntry {
+                        signature: format!("dhancha {} {{ {} }}", name, fields.join(", ")),
+                        doc: doc.clone(),
+                    },
+                ))
+            }
+            Statement::EnumDecl {
+                name,
+                variants,
+                doc,
+                ..
+            } => {
+                let variants: Vec<String> = variants
+                    .iter()
+                    .map(|(variant, payload)| {
+                        if payload.is_empty() {
+                            variant.clone()
+                        } else {
+                            let types: Vec<String> = payload.iter().map(Type::to_string).collect();
+                            format!("{}({})", variant, types.join(", "))
+                        }
+                    })
+                    .collect();
+                Some((
+                    name.clone(),
+                    DocEntry {
+                        signature: format!("prakar {} {{ {} }}", name, variants.join(", ")),
+                        doc: doc.clone(),
+                    },
+                ))
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn repl_forgets_declarations_from_failed_inputs() {
+        let mut interpreter = Interpreter::new();
+        let mut checker = TypeChecker::new();
+        let mut docs = HashMap::new();
+        let mut run = |input: &str| {
+            execute_repl(&mut interpreter, &mut checker, &mut docs, input)
+                .map_err(|errors| errors[0].code())
+        };
+
+        assert_eq!(run(r#"ye a: int = "s";"#), Err("E0101"));
+        assert_eq!(run("paneer.bol(a);"), Err("E0102"));
+        assert_eq!(run("ye b: int = 1 / 0;"), Err("E0202"));
+        assert_eq!(run("ye c: int = b;"), Err("E0102"));
+        assert_eq!(run("ye d: int = 1;"), Ok(()));
+        assert_eq!(run("ye e: int = d + 1;"), Ok(()));
+    }
+
+    #[test]
+    fn repl_adds_missing_semicolons_after_braces_only_when_needed() {
+        let mut interpreter = Interpreter::new();
+        let mut checker = TypeChecker::new();
+        let mut docs = HashMap::new();
+        let mut run = |input: &str| {
+            execute_repl(&mut interpreter, &mut checker, &mut docs, input)
+                .map_err(|errors| errors[0].to_string())
+        };
+
+        assert_eq!(run(r#"ye m: map<string, int> = {"a": 1}  "#), Ok(()));
+        assert_eq!(run(r#"m["b"] = 2"#), Ok(()));
+        assert_eq!(run("dhancha Point { x: int, y: int }"), Ok(()));
+        assert_eq!(run("ye p: Point = Point { x: 1, y: 2 }"), Ok(()));
+        assert_eq!(run("agar m.len() == 2 { p.x = 3; }"), Ok(()));
+        assert_eq!(run("func f() int { wapas kar p.x; }"), Ok(()));
+        assert_eq!(run("ye x: int = f()"), Ok(()));
+    }
+}
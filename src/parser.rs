@@ -1,45 +1,159 @@
 use crate::ast::*;
-use crate::lexer::{Lexer, Token};
-use anyhow::{Result, anyhow};
+use crate::errors::{Expected, PaneerError, Result};
+use crate::lexer::{FormatPart, Lexer, Token};
+use std::cell::OnceCell;
 
 pub struct Parser {
     lexer: Lexer,
+    /// Number of loops enclosing the current statement, used to validate
+    /// `ruko` and `aage badho`
+    loop_depth: usize,
+    /// Whether the current function is declared inside a loop, whose
+    /// `ruko` and `aage badho` cannot reach that loop
+    function_in_loop: bool,
+    /// Syntax errors recovered from so far
+    errors: Vec<PaneerError>,
+    /// Whether `Name {` starts a struct literal, which is turned off in the
+    /// condition of `agar`, `jabtak` and friends where `{` opens the body
+    struct_literals_allowed: bool,
 }
 
 impl Parser {
     pub fn new(lexer: Lexer) -> Self {
-        Parser { lexer }
+        Parser {
+            lexer,
+            loop_depth: 0,
+            function_in_loop: false,
+            errors: Vec::new(),
+            struct_literals_allowed: true,
+        }
     }
 
-    pub fn parse(&mut self) -> Result<Program> {
+    /// Parses the whole token stream into a program
+    ///
+    /// A syntax error does not stop parsing: the broken statement is skipped
+    /// and parsing resumes at the next statement boundary, so one run reports
+    /// every syntax error.
+    ///
+    /// # Returns
+    /// The statements that parsed successfully, and every syntax error in
+    /// source order. The program is only complete if the error list is empty.
+    pub fn parse(&mut self) -> (Program, Vec<PaneerError>) {
         let mut statements = Vec::new();
 
         while !self.lexer.is_at_end() {
-            statements.push(self.parse_statement()?);
+            if let Some(statement) = self.parse_statement_recovering() {
+                statements.push(statement);
+            }
+        }
+
+        (Program { statements }, std::mem::take(&mut self.errors))
+    }
+
+    /// Parses a statement, recording an error and skipping past the broken
+    /// statement instead of failing
+    fn parse_statement_recovering(&mut self) -> Option<Statement> {
+        let start = self.lexer.position();
+        match self.parse_statement() {
+            Ok(statement) => Some(statement),
+            Err(error) => {
+                // A missing ';' is reported once the next statement has been
+                // reached, so there is nothing to skip
+                let missing_semicolon = matches!(
+                    error,
+                    PaneerError::Syntax {
+                        expected: Some(Expected::Semicolon),
+                        ..
+                    }
+                );
+                self.errors.push(error);
+                if !missing_semicolon || self.lexer.position() == start {
+                    self.synchronize(start);
+                }
+                None
+            }
+        }
+    }
+
+    /// Skips tokens until the start of the next statement
+    ///
+    /// Stops after a `;` or `}`, or before a `}`, a keyword that begins a
+    /// statement, `paneer` or a name at the start of a line.
+    fn synchronize(&mut self, start: usize) {
+        // Always make progress, so a token that cannot be parsed is not retried forever
+        if self.lexer.position() == start {
+            self.lexer.advance();
+        }
+
+        if matches!(
+            self.lexer.previous(),
+            Some(Token::Semicolon) | Some(Token::RightBrace)
+        ) {
+            return;
         }
 
-        Ok(Program { statements })
+        while let Some(token) = self.lexer.peek() {
+            match token {
+                Token::Semicolon => {
+                    self.lexer.advance();
+                    return;
+                }
+                Token::RightBrace
+                | Token::Ye
+                | Token::Func
+                | Token::Dhancha
+                | Token::Prakar
+                | Token::Agar
+                | Token::Return
+                | Token::Wapas
+                | Token::Jabtak
+                | Token::Jab
+                | Token::Har
+                | Token::Ruko
+                | Token::Aage
+                | Token::Paneer => return,
+                Token::Identifier(_) if self.lexer.at_line_start() => return,
+                _ => {
+                    self.lexer.advance();
+                }
+            }
+        }
     }
 
     fn parse_statement(&mut self) -> Result<Statement> {
         match self.lexer.peek() {
             Some(Token::Ye) => self.parse_var_declaration(),
             Some(Token::Func) => self.parse_func_declaration(),
+            Some(Token::Dhancha) => self.parse_struct_declaration(),
+            Some(Token::Prakar) => self.parse_enum_declaration(),
             Some(Token::Agar) => self.parse_if_statement(),
             Some(Token::Return) => self.parse_return_statement(),
             Some(Token::Wapas) => self.parse_wapas_kar_statement(),
             Some(Token::Jabtak) => self.parse_while_statement(),
+            Some(Token::Jab) => self.parse_match_statement(),
             Some(Token::Har) => self.parse_for_statement(),
+            Some(Token::Ruko) => self.parse_break_statement(),
+            Some(Token::Aage) => self.parse_continue_statement(),
             _ => self.parse_expression_statement(),
         }
     }
 
     fn parse_var_declaration(&mut self) -> Result<Statement> {
+        let start = self.lexer.peek_span();
+        let doc = self.lexer.doc_comment();
         self.consume(Token::Ye, "Expected 'ye'")?;
 
+        if matches!(self.lexer.peek(), Some(Token::LeftParen)) {
+            return self.parse_tuple_declaration(start);
+        }
+
         let name = match self.lexer.advance() {
             Some(Token::Identifier(name)) => name.clone(),
-            _ => return Err(anyhow!("Expected variable name")),
+            _ => {
+                return Err(
+                    self.expected_at_previous(Expected::VariableName, "Expected variable name")
+                );
+            }
         };
 
         self.consume(Token::Colon, "Expected ':' after variable name")?;
@@ -56,15 +170,59 @@ impl Parser {
             name,
             type_annotation,
             initializer,
+            doc,
+            span: self.span_from(start),
+        })
+    }
+
+    /// Parses the rest of `ye (a, b): (int, string) = value;` after the `ye`
+    fn parse_tuple_declaration(&mut self, start: Span) -> Result<Statement> {
+        self.consume(Token::LeftParen, "Expected '(' before variable names")?;
+
+        let mut names = Vec::new();
+        loop {
+            match self.lexer.advance() {
+                Some(Token::Identifier(name)) => names.push(name.clone()),
+                _ => {
+                    return Err(
+                        self.expected_at_previous(Expected::VariableName, "Expected variable name")
+                    );
+                }
+            }
+            if matches!(self.lexer.peek(), Some(Token::Comma)) {
+                self.lexer.advance();
+            } else {
+                break;
+            }
+        }
+
+        self.consume(Token::RightParen, "Expected ')' after variable names")?;
+        self.consume(Token::Colon, "Expected ':' after variable names")?;
+        let type_annotation = self.parse_type()?;
+        self.consume(Token::Assign, "Expected '=' after type")?;
+        let initializer = self.parse_expression()?;
+        self.consume(Token::Semicolon, "Expected ';' after variable declaration")?;
+
+        Ok(Statement::TupleDecl {
+            names,
+            type_annotation,
+            initializer,
+            span: self.span_from(start),
         })
     }
 
     fn parse_func_declaration(&mut self) -> Result<Statement> {
+        let start = self.lexer.peek_span();
+        let doc = self.lexer.doc_comment();
         self.consume(Token::Func, "Expected 'func'")?;
 
         let name = match self.lexer.advance() {
             Some(Token::Identifier(name)) => name.clone(),
-            _ => return Err(anyhow!("Expected function name")),
+            _ => {
+                return Err(
+                    self.expected_at_previous(Expected::FunctionName, "Expected function name")
+                );
+            }
         };
 
         self.consume(Token::LeftParen, "Expected '(' after function name")?;
@@ -75,7 +233,7 @@ impl Parser {
             loop {
                 let param_name = match self.lexer.advance() {
                     Some(Token::Identifier(name)) => name.clone(),
-                    _ => return Err(anyhow!("Expected parameter name")),
+                    _ => return Err(self.error_at_previous("Expected parameter name")),
                 };
 
                 let param_type = self.parse_type()?;
@@ -91,14 +249,25 @@ impl Parser {
 
         self.consume(Token::RightParen, "Expected ')' after parameters")?;
 
-        let return_type = self.parse_type()?;
+        // A function without a return type returns nothing
+        let return_type = match self.lexer.peek() {
+            Some(Token::LeftBrace) => Type::Unit,
+            Some(Token::KuchNahi) => {
+                self.lexer.advance();
+                Type::Unit
+            }
+            _ => self.parse_type()?,
+        };
 
         self.consume(Token::LeftBrace, "Expected '{' before function body")?;
 
-        let mut body = Vec::new();
-        while !matches!(self.lexer.peek(), Some(Token::RightBrace)) && !self.lexer.is_at_end() {
-            body.push(self.parse_statement()?);
-        }
+        // Loops outside the function cannot be broken out of from inside it
+        let enclosing_loop_depth = std::mem::replace(&mut self.loop_depth, 0);
+        let enclosing_function_in_loop = self.function_in_loop;
+        self.function_in_loop |= enclosing_loop_depth > 0;
+        let body = self.parse_block_statements();
+        self.loop_depth = enclosing_loop_depth;
+        self.function_in_loop = enclosing_function_in_loop;
 
         self.consume(Token::RightBrace, "Expected '}' after function body")?;
 
@@ -107,46 +276,172 @@ impl Parser {
             params,
             return_type,
             body,
+            doc,
+            span: self.span_from(start),
         })
     }
 
-    fn parse_if_statement(&mut self) -> Result<Statement> {
-        self.consume(Token::Agar, "Expected 'agar'")?;
+    fn parse_struct_declaration(&mut self) -> Result<Statement> {
+        let start = self.lexer.peek_span();
+        let doc = self.lexer.doc_comment();
+        self.consume(Token::Dhancha, "Expected 'dhancha'")?;
 
-        let condition = self.parse_expression()?;
+        let name = match self.lexer.advance() {
+            Some(Token::Identifier(name)) => name.clone(),
+            _ => return Err(self.error_at_previous("Expected struct name")),
+        };
 
-        self.consume(Token::LeftBrace, "Expected '{' after if condition")?;
+        self.consume(Token::LeftBrace, "Expected '{' after struct name")?;
 
-        let mut then_branch = Vec::new();
-        while !matches!(self.lexer.peek(), Some(Token::RightBrace)) && !self.lexer.is_at_end() {
-            then_branch.push(self.parse_statement()?);
+        let mut fields: Vec<(String, Type)> = Vec::new();
+        while !matches!(self.lexer.peek(), Some(Token::RightBrace)) {
+            let field = match self.lexer.advance() {
+                Some(Token::Identifier(field)) => field.clone(),
+                _ => return Err(self.error_at_previous("Expected field name")),
+            };
+            if fields.iter().any(|(existing, _)| *existing == field) {
+                return Err(self.error_at_previous(&format!("Duplicate field: {}", field)));
+            }
+            self.consume(Token::Colon, "Expected ':' after field name")?;
+            fields.push((field, self.parse_type()?));
+
+            if matches!(self.lexer.peek(), Some(Token::Comma)) {
+                self.lexer.advance();
+            } else {
+                break;
+            }
         }
 
-        self.consume(Token::RightBrace, "Expected '}' after if body")?;
+        self.consume(Token::RightBrace, "Expected '}' after struct fields")?;
 
-        let else_branch = if matches!(self.lexer.peek(), Some(Token::Varna)) {
-            self.lexer.advance();
-            self.consume(Token::LeftBrace, "Expected '{' after 'varna'")?;
+        Ok(Statement::StructDecl {
+            name,
+            fields,
+            doc,
+            span: self.span_from(start),
+        })
+    }
 
-            let mut else_stmts = Vec::new();
-            while !matches!(self.lexer.peek(), Some(Token::RightBrace)) && !self.lexer.is_at_end() {
-                else_stmts.push(self.parse_statement()?);
-            }
+    fn parse_enum_declaration(&mut self) -> Result<Statement> {
+        let start = self.lexer.peek_span();
+        let doc = self.lexer.doc_comment();
+        self.consume(Token::Prakar, "Expected 'prakar'")?;
 
-            self.consume(Token::RightBrace, "Expected '}' after else body")?;
-            Some(else_stmts)
-        } else {
-            None
+        let name = match self.lexer.advance() {
+            Some(Token::Identifier(name)) => name.clone(),
+            _ => return Err(self.error_at_previous("Expected enum name")),
         };
 
+        self.consume(Token::LeftBrace, "Expected '{' after enum name")?;
+
+        let mut variants: Vec<(String, Vec<Type>)> = Vec::new();
+        while !matches!(self.lexer.peek(), Some(Token::RightBrace)) {
+            let variant = match self.lexer.advance() {
+                Some(Token::Identifier(variant)) => variant.clone(),
+                _ => return Err(self.error_at_previous("Expected variant name")),
+            };
+            if variants.iter().any(|(existing, _)| *existing == variant) {
+                return Err(self.error_at_previous(&format!("Duplicate variant: {}", variant)));
+            }
+
+            let mut payload = Vec::new();
+            if matches!(self.lexer.peek(), Some(Token::LeftParen)) {
+                self.lexer.advance();
+                while !matches!(self.lexer.peek(), Some(Token::RightParen)) {
+                    payload.push(self.parse_type()?);
+                    if matches!(self.lexer.peek(), Some(Token::Comma)) {
+                        self.lexer.advance();
+                    } else {
+                        break;
+                    }
+                }
+                self.consume(Token::RightParen, "Expected ')' after payload types")?;
+            }
+            variants.push((variant, payload));
+
+            if matches!(self.lexer.peek(), Some(Token::Comma)) {
+                self.lexer.advance();
+            } else {
+                break;
+            }
+        }
+
+        self.consume(Token::RightBrace, "Expected '}' after enum variants")?;
+
+        Ok(Statement::EnumDecl {
+            name,
+            variants,
+            doc,
+            span: self.span_from(start),
+        })
+    }
+
+    fn parse_if_statement(&mut self) -> Result<Statement> {
+        let start = self.lexer.peek_span();
+        self.consume(Token::Agar, "Expected 'agar'")?;
+
+        // `agar ye pattern = value` tests a value against a pattern
+        if matches!(self.lexer.peek(), Some(Token::Ye)) {
+            self.lexer.advance();
+            let pattern = self.parse_pattern()?;
+            self.consume(Token::Assign, "Expected '=' after pattern")?;
+            let value = self.parse_condition()?;
+
+            self.consume(Token::LeftBrace, "Expected '{' after value to match")?;
+            let then_branch = self.parse_block_statements();
+            self.consume(Token::RightBrace, "Expected '}' after if body")?;
+
+            let else_branch = self.parse_else_branch()?;
+            return Ok(Statement::IfLetStmt {
+                pattern,
+                value,
+                then_branch,
+                else_branch,
+                span: self.span_from(start),
+            });
+        }
+
+        let condition = self.parse_condition()?;
+
+        self.consume(Token::LeftBrace, "Expected '{' after if condition")?;
+
+        let then_branch = self.parse_block_statements();
+
+        self.consume(Token::RightBrace, "Expected '}' after if body")?;
+
+        let else_branch = self.parse_else_branch()?;
+
         Ok(Statement::IfStmt {
             condition,
             then_branch,
             else_branch,
+            span: self.span_from(start),
         })
     }
 
+    /// Parses an optional `varna { ... }` or `varna agar ...` after an if body
+    fn parse_else_branch(&mut self) -> Result<Option<Vec<Statement>>> {
+        if !matches!(self.lexer.peek(), Some(Token::Varna)) {
+            return Ok(None);
+        }
+        self.lexer.advance();
+
+        // `varna agar` continues the chain with a nested if statement
+        if matches!(self.lexer.peek(), Some(Token::Agar)) {
+            let else_if = self.parse_if_statement()?;
+            return Ok(Some(vec![else_if]));
+        }
+
+        self.consume(Token::LeftBrace, "Expected '{' after 'varna'")?;
+
+        let else_stmts = self.parse_block_statements();
+
+        self.consume(Token::RightBrace, "Expected '}' after else body")?;
+        Ok(Some(else_stmts))
+    }
+
     fn parse_return_statement(&mut self) -> Result<Statement> {
+        let start = self.lexer.peek_span();
         self.consume(Token::Return, "Expected 'return'")?;
 
         let value = if matches!(self.lexer.peek(), Some(Token::Semicolon)) {
@@ -157,10 +452,14 @@ impl Parser {
 
         self.consume(Token::Semicolon, "Expected ';' after return statement")?;
 
-        Ok(Statement::ReturnStmt { value })
+        Ok(Statement::ReturnStmt {
+            value,
+            span: self.span_from(start),
+        })
     }
 
     fn parse_wapas_kar_statement(&mut self) -> Result<Statement> {
+        let start = self.lexer.peek_span();
         self.consume(Token::Wapas, "Expected 'wapas'")?;
         self.consume(Token::Kar, "Expected 'kar' after 'wapas'")?;
 
@@ -172,62 +471,466 @@ impl Parser {
 
         self.consume(Token::Semicolon, "Expected ';' after wapas kar statement")?;
 
-        Ok(Statement::ReturnStmt { value })
+        Ok(Statement::ReturnStmt {
+            value,
+            span: self.span_from(start),
+        })
     }
 
     fn parse_while_statement(&mut self) -> Result<Statement> {
+        let start = self.lexer.peek_span();
         self.consume(Token::Jabtak, "Expected 'jabtak'")?;
 
-        let condition = self.parse_expression()?;
+        let condition = self.parse_condition()?;
 
         self.consume(Token::LeftBrace, "Expected '{' after while condition")?;
 
-        let mut body = Vec::new();
-        while !matches!(self.lexer.peek(), Some(Token::RightBrace)) && !self.lexer.is_at_end() {
-            body.push(self.parse_statement()?);
-        }
+        let body = self.parse_loop_body();
 
         self.consume(Token::RightBrace, "Expected '}' after while body")?;
 
-        Ok(Statement::WhileStmt { condition, body })
+        Ok(Statement::WhileStmt {
+            condition,
+            body,
+            span: self.span_from(start),
+        })
     }
 
     fn parse_for_statement(&mut self) -> Result<Statement> {
+        let start = self.lexer.peek_span();
         self.consume(Token::Har, "Expected 'har'")?;
 
         let variable = match self.lexer.advance() {
             Some(Token::Identifier(name)) => name.clone(),
-            _ => return Err(anyhow!("Expected variable name after 'har'")),
+            _ => {
+                return Err(self.expected_at_previous(
+                    Expected::VariableName,
+                    "Expected variable name after 'har'",
+                ));
+            }
+        };
+
+        if matches!(self.lexer.peek(), Some(Token::Se)) {
+            return self.parse_range_for_statement(start, variable);
+        }
+
+        // `har key, value mein m` names both halves of each entry
+        let value_variable = if matches!(self.lexer.peek(), Some(Token::Comma)) {
+            self.lexer.advance();
+            match self.lexer.advance() {
+                Some(Token::Identifier(name)) => Some(name.clone()),
+                _ => {
+                    return Err(self.expected_at_previous(
+                        Expected::VariableName,
+                        "Expected variable name after ','",
+                    ));
+                }
+            }
+        } else {
+            None
         };
 
         self.consume(Token::Mein, "Expected 'mein' after variable")?;
 
-        let iterable = self.parse_expression()?;
+        let iterable = self.parse_condition()?;
 
         self.consume(Token::LeftBrace, "Expected '{' after for expression")?;
 
-        let mut body = Vec::new();
-        while !matches!(self.lexer.peek(), Some(Token::RightBrace)) && !self.lexer.is_at_end() {
-            body.push(self.parse_statement()?);
-        }
+        let body = self.parse_loop_body();
 
         self.consume(Token::RightBrace, "Expected '}' after for body")?;
 
         Ok(Statement::ForStmt {
             variable,
+            value_variable,
+            variable_type: OnceCell::new(),
+            value_type: OnceCell::new(),
             iterable,
             body,
+            span: self.span_from(start),
+        })
+    }
+
+    fn parse_range_for_statement(&mut self, start: Span, variable: String) -> Result<Statement> {
+        self.consume(Token::Se, "Expected 'se' after variable")?;
+
+        let range_start = self.parse_condition()?;
+
+        self.consume(Token::Tak, "Expected 'tak' after range start")?;
+
+        let end = self.parse_condition()?;
+
+        let step = if matches!(self.lexer.peek(), Some(Token::Kadam)) {
+            self.lexer.advance();
+            Some(self.parse_condition()?)
+        } else {
+            None
+        };
+
+        self.consume(Token::LeftBrace, "Expected '{' after range")?;
+
+        let body = self.parse_loop_body();
+
+        self.consume(Token::RightBrace, "Expected '}' after for body")?;
+
+        Ok(Statement::RangeForStmt {
+            variable,
+            start: range_start,
+            end,
+            step,
+            body,
+            span: self.span_from(start),
+        })
+    }
+
+    /// Parses the statements of a loop body up to (but not including) the closing '}'
+    fn parse_loop_body(&mut self) -> Vec<Statement> {
+        self.loop_depth += 1;
+        let body = self.parse_block_statements();
+        self.loop_depth -= 1;
+        body
+    }
+
+    /// Parses statements up to (but not including) the closing '}' of a block,
+    /// recovering from errors in individual statements
+    fn parse_block_statements(&mut self) -> Vec<Statement> {
+        let mut statements = Vec::new();
+        while !matches!(self.lexer.peek(), Some(Token::RightBrace)) && !self.lexer.is_at_end() {
+            if let Some(statement) = self.parse_statement_recovering() {
+                statements.push(statement);
+            }
+        }
+        statements
+    }
+
+    fn parse_break_statement(&mut self) -> Result<Statement> {
+        let start = self.lexer.peek_span();
+        self.consume(Token::Ruko, "Expected 'ruko'")?;
+
+        if self.loop_depth == 0 {
+            return Err(PaneerError::loop_control(
+                "'ruko' can only be used inside a loop",
+                self.function_in_loop,
+            )
+            .at(self.lexer.previous_span()));
+        }
+
+        self.consume(Token::Semicolon, "Expected ';' after ruko")?;
+
+        Ok(Statement::BreakStmt {
+            span: self.span_from(start),
+        })
+    }
+
+    fn parse_continue_statement(&mut self) -> Result<Statement> {
+        let start = self.lexer.peek_span();
+        self.consume(Token::Aage, "Expected 'aage'")?;
+        self.consume(Token::Badho, "Expected 'badho' after 'aage'")?;
+
+        if self.loop_depth == 0 {
+            return Err(PaneerError::loop_control(
+                "'aage badho' can only be used inside a loop",
+                self.function_in_loop,
+            )
+            .at(self.span_from(start)));
+        }
+
+        self.consume(Token::Semicolon, "Expected ';' after aage badho")?;
+
+        Ok(Statement::ContinueStmt {
+            span: self.span_from(start),
+        })
+    }
+
+    fn parse_match_statement(&mut self) -> Result<Statement> {
+        let start = self.lexer.peek_span();
+        self.consume(Token::Jab, "Expected 'jab'")?;
+
+        let subject = self.parse_condition()?;
+        self.consume(Token::LeftBrace, "Expected '{' after value to match")?;
+
+        let mut arms = Vec::new();
+        while !matches!(self.lexer.peek(), Some(Token::RightBrace)) && !self.lexer.is_at_end() {
+            arms.push(self.parse_match_arm()?);
+        }
+
+        self.consume(Token::RightBrace, "Expected '}' after match arms")?;
+
+        Ok(Statement::MatchStmt {
+            subject,
+            arms,
+            span: self.span_from(start),
+        })
+    }
+
+    /// Parses `pattern agar guard => body`, where the body is a block or a
+    /// single statement
+    fn parse_match_arm(&mut self) -> Result<MatchArm> {
+        let start = self.lexer.peek_span();
+        let pattern = self.parse_pattern()?;
+
+        let guard = if matches!(self.lexer.peek(), Some(Token::Agar)) {
+            self.lexer.advance();
+            Some(self.parse_expression()?)
+        } else {
+            None
+        };
+
+        self.consume(Token::FatArrow, "Expected '=>' after pattern")?;
+
+        let body = if matches!(self.lexer.peek(), Some(Token::LeftBrace)) {
+            self.lexer.advance();
+            let body = self.parse_block_statements();
+            self.consume(Token::RightBrace, "Expected '}' after match arm")?;
+            body
+        } else {
+            vec![self.parse_statement()?]
+        };
+
+        Ok(MatchArm {
+            pattern,
+            guard,
+            body,
+            span: self.span_from(start),
+        })
+    }
+
+    fn parse_pattern(&mut self) -> Result<Pattern> {
+        let start = self.lexer.peek_span();
+
+        match self.lexer.peek() {
+            Some(Token::Identifier(name)) => {
+                let name = name.clone();
+                self.lexer.advance();
+                if matches!(self.lexer.peek(), Some(Token::ColonColon)) {
+                    return self.parse_variant_pattern(name, start);
+                }
+                return Ok(if name == "_" {
+                    Pattern::Wildcard { span: start }
+                } else {
+                    Pattern::Binding {
+                        name,
+                        binding_type: OnceCell::new(),
+                        span: start,
+                    }
+                });
+            }
+            Some(Token::LeftBracket) => {
+                self.lexer.advance();
+                return self.parse_array_pattern(start);
+            }
+            _ => {}
+        }
+
+        let value = self.parse_pattern_literal()?;
+        if matches!(self.lexer.peek(), Some(Token::Tak)) {
+            self.lexer.advance();
+            let end = self.parse_pattern_literal()?;
+            return Ok(Pattern::Range {
+                start: value,
+                end,
+                span: self.span_from(start),
+            });
+        }
+
+        Ok(Pattern::Literal {
+            value,
+            span: self.span_from(start),
+        })
+    }
+
+    /// Parses the rest of `Enum::Variant(a, b)` after the enum name
+    fn parse_variant_pattern(&mut self, enum_name: String, start: Span) -> Result<Pattern> {
+        self.consume(Token::ColonColon, "Expected '::' after enum name")?;
+        let variant = match self.lexer.advance() {
+            Some(Token::Identifier(variant)) => variant.clone(),
+            _ => return Err(self.error_at_previous("Expected variant name after '::'")),
+        };
+
+        let mut payload = Vec::new();
+        if matches!(self.lexer.peek(), Some(Token::LeftParen)) {
+            self.lexer.advance();
+            while !matches!(self.lexer.peek(), Some(Token::RightParen)) {
+                payload.push(self.parse_pattern()?);
+                if matches!(self.lexer.peek(), Some(Token::Comma)) {
+                    self.lexer.advance();
+                } else {
+                    break;
+                }
+            }
+            self.consume(Token::RightParen, "Expected ')' after variant patterns")?;
+        }
+
+        Ok(Pattern::Variant {
+            enum_name,
+            variant,
+            payload,
+            span: self.span_from(start),
+        })
+    }
+
+    /// Parses a literal in a pattern, including negative numbers such as `-1`
+    fn parse_pattern_literal(&mut self) -> Result<LiteralValue> {
+        match self.parse_unary()? {
+            Expression::Literal { value, .. } => Ok(value),
+            other => Err(PaneerError::syntax(
+                "Expected a pattern: a literal, a range, a name, '_', an array or an enum variant",
+            )
+            .at(other.span())),
+        }
+    }
+
+    /// Parses the rest of `[first, second, ..rest]` after the `[`
+    fn parse_array_pattern(&mut self, start: Span) -> Result<Pattern> {
+        let mut elements = Vec::new();
+        let mut rest = None;
+
+        while !matches!(self.lexer.peek(), Some(Token::RightBracket)) {
+            if matches!(self.lexer.peek(), Some(Token::DotDot)) {
+                self.lexer.advance();
+                let name = match self.lexer.peek() {
+                    Some(Token::Identifier(name)) => {
+                        let name = (name != "_").then(|| name.clone());
+                        self.lexer.advance();
+                        name
+                    }
+                    _ => None,
+                };
+                rest = Some(name);
+
+                if !matches!(self.lexer.peek(), Some(Token::RightBracket)) {
+                    return Err(
+                        self.error_at_current("'..' must be the last item in an array pattern")
+                    );
+                }
+                break;
+            }
+
+            elements.push(self.parse_pattern()?);
+
+            if matches!(self.lexer.peek(), Some(Token::Comma)) {
+                self.lexer.advance();
+            } else {
+                break;
+            }
+        }
+
+        self.consume(Token::RightBracket, "Expected ']' after array pattern")?;
+
+        Ok(Pattern::Array {
+            elements,
+            rest,
+            rest_type: OnceCell::new(),
+            span: self.span_from(start),
         })
     }
 
     fn parse_expression_statement(&mut self) -> Result<Statement> {
+        let start = self.lexer.peek_span();
         let expr = self.parse_expression()?;
+
+        let operator = match self.lexer.peek() {
+            Some(Token::Assign) => Some(None),
+            Some(Token::PlusAssign) | Some(Token::PlusPlus) => Some(Some(BinaryOperator::Add)),
+            Some(Token::MinusAssign) | Some(Token::MinusMinus) => {
+                Some(Some(BinaryOperator::Subtract))
+            }
+            Some(Token::StarAssign) => Some(Some(BinaryOperator::Multiply)),
+            Some(Token::SlashAssign) => Some(Some(BinaryOperator::Divide)),
+            _ => None,
+        };
+
+        if let Some(operator) = operator {
+            if !matches!(
+                expr,
+                Expression::Variable { .. }
+                    | Expression::ArrayAccess { .. }
+                    | Expression::FieldAccess { .. }
+            ) {
+                return Err(PaneerError::syntax("Invalid assignment target").at(expr.span()));
+            }
+
+            // `x++` and `x--` are shorthand for `x += 1` and `x -= 1`
+            let value = match self.lexer.advance() {
+                Some(Token::PlusPlus) | Some(Token::MinusMinus) => Expression::Literal {
+                    value: LiteralValue::Int(1),
+                    span: self.lexer.previous_span(),
+                },
+                _ => self.parse_expression()?,
+            };
+
+            self.consume(Token::Semicolon, "Expected ';' after assignment")?;
+            return Ok(Statement::Assignment {
+                target: expr,
+                operator,
+                value,
+                span: self.span_from(start),
+            });
+        }
+
         self.consume(Token::Semicolon, "Expected ';' after expression")?;
-        Ok(Statement::ExprStmt { expression: expr })
+        Ok(Statement::ExprStmt {
+            expression: expr,
+            span: self.span_from(start),
+        })
     }
 
     fn parse_expression(&mut self) -> Result<Expression> {
-        self.parse_equality()
+        self.parse_coalesce()
+    }
+
+    fn parse_coalesce(&mut self) -> Result<Expression> {
+        let mut expr = self.parse_or()?;
+
+        while matches!(self.lexer.peek(), Some(Token::QuestionQuestion)) {
+            self.lexer.advance();
+            let right = self.parse_or()?;
+            let span = expr.span().to(right.span());
+            expr = Expression::Binary {
+                left: Box::new(expr),
+                operator: BinaryOperator::Coalesce,
+                right: Box::new(right),
+                span,
+            };
+        }
+
+        Ok(expr)
+    }
+
+    fn parse_or(&mut self) -> Result<Expression> {
+        let mut expr = self.parse_and()?;
+
+        while matches!(self.lexer.peek(), Some(Token::Or)) {
+            self.lexer.advance();
+            let right = self.parse_and()?;
+            let span = expr.span().to(right.span());
+            expr = Expression::Binary {
+                left: Box::new(expr),
+                operator: BinaryOperator::Or,
+                right: Box::new(right),
+                span,
+            };
+        }
+
+        Ok(expr)
+    }
+
+    fn parse_and(&mut self) -> Result<Expression> {
+        let mut expr = self.parse_equality()?;
+
+        while matches!(self.lexer.peek(), Some(Token::And)) {
+            self.lexer.advance();
+            let right = self.parse_equality()?;
+            let span = expr.span().to(right.span());
+            expr = Expression::Binary {
+                left: Box::new(expr),
+                operator: BinaryOperator::And,
+                right: Box::new(right),
+                span,
+            };
+        }
+
+        Ok(expr)
     }
 
     fn parse_equality(&mut self) -> Result<Expression> {
@@ -242,10 +945,12 @@ impl Parser {
 
             self.lexer.advance();
             let right = self.parse_comparison()?;
+            let span = expr.span().to(right.span());
             expr = Expression::Binary {
                 left: Box::new(expr),
                 operator,
                 right: Box::new(right),
+                span,
             };
         }
 
@@ -253,7 +958,7 @@ impl Parser {
     }
 
     fn parse_comparison(&mut self) -> Result<Expression> {
-        let mut expr = self.parse_term()?;
+        let mut expr = self.parse_bit_or()?;
 
         while let Some(token) = self.lexer.peek() {
             let operator = match token {
@@ -264,12 +969,92 @@ impl Parser {
                 _ => break,
             };
 
+            self.lexer.advance();
+            let right = self.parse_bit_or()?;
+            let span = expr.span().to(right.span());
+            expr = Expression::Binary {
+                left: Box::new(expr),
+                operator,
+                right: Box::new(right),
+                span,
+            };
+        }
+
+        Ok(expr)
+    }
+
+    fn parse_bit_or(&mut self) -> Result<Expression> {
+        let mut expr = self.parse_bit_xor()?;
+
+        while matches!(self.lexer.peek(), Some(Token::Pipe)) {
+            self.lexer.advance();
+            let right = self.parse_bit_xor()?;
+            let span = expr.span().to(right.span());
+            expr = Expression::Binary {
+                left: Box::new(expr),
+                operator: BinaryOperator::BitOr,
+                right: Box::new(right),
+                span,
+            };
+        }
+
+        Ok(expr)
+    }
+
+    fn parse_bit_xor(&mut self) -> Result<Expression> {
+        let mut expr = self.parse_bit_and()?;
+
+        while matches!(self.lexer.peek(), Some(Token::Caret)) {
+            self.lexer.advance();
+            let right = self.parse_bit_and()?;
+            let span = expr.span().to(right.span());
+            expr = Expression::Binary {
+                left: Box::new(expr),
+                operator: BinaryOperator::BitXor,
+                right: Box::new(right),
+                span,
+            };
+        }
+
+        Ok(expr)
+    }
+
+    fn parse_bit_and(&mut self) -> Result<Expression> {
+        let mut expr = self.parse_shift()?;
+
+        while matches!(self.lexer.peek(), Some(Token::Ampersand)) {
+            self.lexer.advance();
+            let right = self.parse_shift()?;
+            let span = expr.span().to(right.span());
+            expr = Expression::Binary {
+                left: Box::new(expr),
+                operator: BinaryOperator::BitAnd,
+                right: Box::new(right),
+                span,
+            };
+        }
+
+        Ok(expr)
+    }
+
+    fn parse_shift(&mut self) -> Result<Expression> {
+        let mut expr = self.parse_term()?;
+
+        while let Some(token) = self.lexer.peek() {
+            let operator = match token {
+                Token::ShiftLeft => BinaryOperator::ShiftLeft,
+                Token::ShiftRight => BinaryOperator::ShiftRight,
+                _ => break,
+            };
+
             self.lexer.advance();
             let right = self.parse_term()?;
+            let span = expr.span().to(right.span());
             expr = Expression::Binary {
                 left: Box::new(expr),
                 operator,
                 right: Box::new(right),
+                span,
             };
         }
 
@@ -288,10 +1073,12 @@ impl Parser {
 
             self.lexer.advance();
             let right = self.parse_factor()?;
+            let span = expr.span().to(right.span());
             expr = Expression::Binary {
                 left: Box::new(expr),
                 operator,
                 right: Box::new(right),
+                span,
             };
         }
 
@@ -305,15 +1092,18 @@ impl Parser {
             let operator = match token {
                 Token::Star => BinaryOperator::Multiply,
                 Token::Slash => BinaryOperator::Divide,
+                Token::Percent => BinaryOperator::Modulo,
                 _ => break,
             };
 
             self.lexer.advance();
             let right = self.parse_unary()?;
+            let span = expr.span().to(right.span());
             expr = Expression::Binary {
                 left: Box::new(expr),
                 operator,
                 right: Box::new(right),
+                span,
             };
         }
 
@@ -321,25 +1111,89 @@ impl Parser {
     }
 
     fn parse_unary(&mut self) -> Result<Expression> {
+        let start = self.lexer.peek_span();
         match self.lexer.peek() {
             Some(Token::Bang) => {
                 self.lexer.advance();
                 let operand = self.parse_unary()?;
                 Ok(Expression::Unary {
                     operator: UnaryOperator::Not,
+                    span: start.to(operand.span()),
                     operand: Box::new(operand),
                 })
             }
             Some(Token::Minus) => {
                 self.lexer.advance();
+
+                // `i64::MIN` has no positive counterpart, so it can only be
+                // written as a negated literal
+                if let Some(Token::IntLiteral(value)) = self.lexer.peek()
+                    && *value == i64::MIN.unsigned_abs()
+                    && !matches!(self.lexer.peek_nth(1), Some(Token::StarStar))
+                {
+                    let span = start.to(self.lexer.peek_span());
+                    self.lexer.advance();
+                    return Ok(Expression::Literal {
+                        value: LiteralValue::Int(i64::MIN),
+                        span,
+                    });
+                }
+
                 let operand = self.parse_unary()?;
-                Ok(Expression::Unary {
-                    operator: UnaryOperator::Minus,
-                    operand: Box::new(operand),
-                })
+                let span = start.to(operand.span());
+
+                // Fold negated numeric literals into constants
+                match operand {
+                    Expression::Literal {
+                        value: LiteralValue::Int(value),
+                        ..
+                    } => match value.checked_neg() {
+                        Some(value) => Ok(Expression::Literal {
+                            value: LiteralValue::Int(value),
+                            span,
+                        }),
+                        None => Err(PaneerError::overflow(format!(
+                            "Integer overflow: -({})",
+                            value
+                        ))
+                        .at(span)),
+                    },
+                    Expression::Literal {
+                        value: LiteralValue::Float(value),
+                        ..
+                    } => Ok(Expression::Literal {
+                        value: LiteralValue::Float(-value),
+                        span,
+                    }),
+                    operand => Ok(Expression::Unary {
+                        operator: UnaryOperator::Minus,
+                        operand: Box::new(operand),
+                        span,
+                    }),
+                }
             }
-            _ => self.parse_call(),
+            _ => self.parse_power(),
+        }
+    }
+
+    /// Parses `**`, which is right-associative and binds tighter than unary
+    /// operators on its left, so `-2 ** 2` is `-(2 ** 2)`
+    fn parse_power(&mut self) -> Result<Expression> {
+        let base = self.parse_call()?;
+
+        if matches!(self.lexer.peek(), Some(Token::StarStar)) {
+            self.lexer.advance();
+            let exponent = self.parse_unary()?;
+            let span = base.span().to(exponent.span());
+            return Ok(Expression::Binary {
+                left: Box::new(base),
+                operator: BinaryOperator::Power,
+                right: Box::new(exponent),
+                span,
+            });
         }
+
+        Ok(base)
     }
 
     fn parse_call(&mut self) -> Result<Expression> {
@@ -353,7 +1207,7 @@ impl Parser {
 
                     if !matches!(self.lexer.peek(), Some(Token::RightParen)) {
                         loop {
-                            arguments.push(self.parse_expression()?);
+                            arguments.push(self.parse_enclosed_expression()?);
                             if matches!(self.lexer.peek(), Some(Token::Comma)) {
                                 self.lexer.advance();
                             } else {
@@ -364,25 +1218,55 @@ impl Parser {
 
                     self.consume(Token::RightParen, "Expected ')' after arguments")?;
 
+                    let span = self.span_from(expr.span());
                     expr = Expression::Call {
                         callee: Box::new(expr),
                         arguments,
+                        span,
                     };
                 }
                 Some(Token::Dot) => {
                     self.lexer.advance();
+
+                    // `pair.0` reads a tuple element
+                    if let Some(Token::IntLiteral(index)) = self.lexer.peek() {
+                        let index = *index as usize;
+                        self.lexer.advance();
+                        let span = self.span_from(expr.span());
+                        expr = Expression::TupleAccess {
+                            tuple: Box::new(expr),
+                            index,
+                            span,
+                        };
+                        continue;
+                    }
+
                     let method = match self.lexer.advance() {
                         Some(Token::Identifier(name)) => name.clone(),
                         Some(Token::Bol) => "bol".to_string(), // Special case for paneer.bol
-                        _ => return Err(anyhow!("Expected method name after '.'")),
+                        _ => {
+                            return Err(
+                                self.error_at_previous("Expected field or method name after '.'")
+                            );
+                        }
                     };
 
-                    self.consume(Token::LeftParen, "Expected '(' after method name")?;
+                    // Without an argument list this is a field access
+                    if !matches!(self.lexer.peek(), Some(Token::LeftParen)) {
+                        let span = self.span_from(expr.span());
+                        expr = Expression::FieldAccess {
+                            object: Box::new(expr),
+                            field: method,
+                            span,
+                        };
+                        continue;
+                    }
+                    self.lexer.advance();
 
                     let mut arguments = Vec::new();
                     if !matches!(self.lexer.peek(), Some(Token::RightParen)) {
                         loop {
-                            arguments.push(self.parse_expression()?);
+                            arguments.push(self.parse_enclosed_expression()?);
                             if matches!(self.lexer.peek(), Some(Token::Comma)) {
                                 self.lexer.advance();
                             } else {
@@ -393,20 +1277,24 @@ impl Parser {
 
                     self.consume(Token::RightParen, "Expected ')' after method arguments")?;
 
+                    let span = self.span_from(expr.span());
                     expr = Expression::MethodCall {
                         object: Box::new(expr),
                         method,
                         arguments,
+                        span,
                     };
                 }
                 Some(Token::LeftBracket) => {
                     self.lexer.advance();
-                    let index = self.parse_expression()?;
+                    let index = self.parse_enclosed_expression()?;
                     self.consume(Token::RightBracket, "Expected ']' after array index")?;
 
+                    let span = self.span_from(expr.span());
                     expr = Expression::ArrayAccess {
                         array: Box::new(expr),
                         index: Box::new(index),
+                        span,
                     };
                 }
                 _ => break,
@@ -417,37 +1305,72 @@ impl Parser {
     }
 
     fn parse_primary(&mut self) -> Result<Expression> {
-        match self.lexer.advance() {
-            Some(Token::True) => Ok(Expression::Literal {
-                value: LiteralValue::Bool(true),
-            }),
-            Some(Token::False) => Ok(Expression::Literal {
-                value: LiteralValue::Bool(false),
-            }),
-            Some(Token::IntLiteral(value)) => Ok(Expression::Literal {
-                value: LiteralValue::Int(*value),
-            }),
-            Some(Token::FloatLiteral(value)) => Ok(Expression::Literal {
-                value: LiteralValue::Float(*value),
-            }),
-            Some(Token::StringLiteral(value)) => Ok(Expression::Literal {
-                value: LiteralValue::String(value.clone()),
-            }),
-            Some(Token::Identifier(name)) => Ok(Expression::Variable { name: name.clone() }),
-            Some(Token::Paneer) => Ok(Expression::Variable {
-                name: "paneer".to_string(),
-            }),
+        let start = self.lexer.peek_span();
+        let literal = match self.lexer.advance() {
+            Some(Token::True) => LiteralValue::Bool(true),
+            Some(Token::False) => LiteralValue::Bool(false),
+            Some(Token::Khaali) => LiteralValue::Khaali,
+            Some(Token::IntLiteral(value)) => match i64::try_from(*value) {
+                Ok(value) => LiteralValue::Int(value),
+                Err(_) => {
+                    return Err(PaneerError::overflow(format!(
+                        "Integer literal {} is too large for int (maximum is {})",
+                        value,
+                        i64::MAX
+                    ))
+                    .at(start));
+                }
+            },
+            Some(Token::FloatLiteral(value)) => LiteralValue::Float(*value),
+            Some(Token::StringLiteral(value)) => LiteralValue::String(value.clone()),
+            Some(Token::FormatString(parts)) => {
+                let parts = parts.clone();
+                return self.parse_interpolated_string(parts, start);
+            }
+            Some(Token::Identifier(name)) => {
+                let name = name.clone();
+                if matches!(self.lexer.peek(), Some(Token::ColonColon)) {
+                    return self.parse_enum_variant(name, start);
+                }
+                if self.struct_literals_allowed
+                    && matches!(self.lexer.peek(), Some(Token::LeftBrace))
+                {
+                    return self.parse_struct_literal(name, start);
+                }
+                return Ok(Expression::Variable { name, span: start });
+            }
+            Some(Token::Paneer) => {
+                return Ok(Expression::Variable {
+                    name: "paneer".to_string(),
+                    span: start,
+                });
+            }
             Some(Token::LeftParen) => {
-                let expr = self.parse_expression()?;
-                self.consume(Token::RightParen, "Expected ')' after expression")?;
-                Ok(expr)
+                let expr = self.parse_enclosed_expression()?;
+                if !matches!(self.lexer.peek(), Some(Token::Comma)) {
+                    self.consume(Token::RightParen, "Expected ')' after expression")?;
+                    return Ok(expr);
+                }
+
+                // A comma makes it a tuple: `(a, b)`
+                let mut elements = vec![expr];
+                while matches!(self.lexer.peek(), Some(Token::Comma)) {
+                    self.lexer.advance();
+                    elements.push(self.parse_enclosed_expression()?);
+                }
+                self.consume(Token::RightParen, "Expected ')' after tuple elements")?;
+                return Ok(Expression::TupleLiteral {
+                    elements,
+                    span: self.span_from(start),
+                });
             }
+            Some(Token::Agar) => return self.parse_conditional(start),
             Some(Token::LeftBracket) => {
                 let mut elements = Vec::new();
 
                 if !matches!(self.lexer.peek(), Some(Token::RightBracket)) {
                     loop {
-                        elements.push(self.parse_expression()?);
+                        elements.push(self.parse_enclosed_expression()?);
                         if matches!(self.lexer.peek(), Some(Token::Comma)) {
                             self.lexer.advance();
                         } else {
@@ -458,13 +1381,181 @@ impl Parser {
 
                 self.consume(Token::RightBracket, "Expected ']' after array elements")?;
 
-                Ok(Expression::ArrayLiteral { elements })
+                return Ok(Expression::ArrayLiteral {
+                    elements,
+                    span: self.span_from(start),
+                });
+            }
+            Some(Token::LeftBrace) => {
+                let mut entries = Vec::new();
+
+                while !matches!(self.lexer.peek(), Some(Token::RightBrace)) {
+                    let key = self.parse_enclosed_expression()?;
+                    self.consume(Token::Colon, "Expected ':' after map key")?;
+                    let value = self.parse_enclosed_expression()?;
+                    entries.push((key, value));
+
+                    if matches!(self.lexer.peek(), Some(Token::Comma)) {
+                        self.lexer.advance();
+                    } else {
+                        break;
+                    }
+                }
+
+                self.consume(Token::RightBrace, "Expected '}' after map entries")?;
+
+                return Ok(Expression::MapLiteral {
+                    entries,
+                    span: self.span_from(start),
+                });
+            }
+            _ => {
+                return Err(self.expected_at_previous(Expected::Expression, "Expected expression"));
+            }
+        };
+
+        Ok(Expression::Literal {
+            value: literal,
+            span: start,
+        })
+    }
+
+    /// Parses the fields of `Name { field: value, ... }` after the name
+    fn parse_struct_literal(&mut self, name: String, start: Span) -> Result<Expression> {
+        self.consume(Token::LeftBrace, "Expected '{' after struct name")?;
+
+        let mut fields = Vec::new();
+        while !matches!(self.lexer.peek(), Some(Token::RightBrace)) {
+            let field = match self.lexer.advance() {
+                Some(Token::Identifier(field)) => field.clone(),
+                _ => return Err(self.error_at_previous("Expected field name")),
+            };
+            self.consume(Token::Colon, "Expected ':' after field name")?;
+            fields.push((field, self.parse_enclosed_expression()?));
+
+            if matches!(self.lexer.peek(), Some(Token::Comma)) {
+                self.lexer.advance();
+            } else {
+                break;
             }
-            _ => Err(anyhow!("Expected expression")),
         }
+
+        self.consume(Token::RightBrace, "Expected '}' after struct fields")?;
+
+        Ok(Expression::StructLiteral {
+            name,
+            fields,
+            span: self.span_from(start),
+        })
+    }
+
+    /// Parses the rest of `Enum::Variant(args)` after the enum name
+    fn parse_enum_variant(&mut self, enum_name: String, start: Span) -> Result<Expression> {
+        self.consume(Token::ColonColon, "Expected '::' after enum name")?;
+        let variant = match self.lexer.advance() {
+            Some(Token::Identifier(variant)) => variant.clone(),
+            _ => return Err(self.error_at_previous("Expected variant name after '::'")),
+        };
+
+        let mut arguments = Vec::new();
+        if matches!(self.lexer.peek(), Some(Token::LeftParen)) {
+            self.lexer.advance();
+            while !matches!(self.lexer.peek(), Some(Token::RightParen)) {
+                arguments.push(self.parse_enclosed_expression()?);
+                if matches!(self.lexer.peek(), Some(Token::Comma)) {
+                    self.lexer.advance();
+                } else {
+                    break;
+                }
+            }
+            self.consume(Token::RightParen, "Expected ')' after variant arguments")?;
+        }
+
+        Ok(Expression::EnumVariant {
+            enum_name,
+            variant,
+            arguments,
+            span: self.span_from(start),
+        })
+    }
+
+    /// Parses an expression that is directly followed by a `{` block, such as
+    /// the condition of `agar`, so that `x {` is not read as a struct literal
+    fn parse_condition(&mut self) -> Result<Expression> {
+        let allowed = std::mem::replace(&mut self.struct_literals_allowed, false);
+        let result = self.parse_expression();
+        self.struct_literals_allowed = allowed;
+        result
+    }
+
+    /// Parses an expression inside `()`, `[]` or `{}`, where struct literals
+    /// are unambiguous even within a condition
+    fn parse_enclosed_expression(&mut self) -> Result<Expression> {
+        let allowed = std::mem::replace(&mut self.struct_literals_allowed, true);
+        let result = self.parse_expression();
+        self.struct_literals_allowed = allowed;
+        result
+    }
+
+    /// Parses `agar condition toh a varna b` after the `agar`
+    ///
+    /// Each arm is a full expression, so `varna` takes everything up to the end
+    /// of the enclosing expression; wrap the conditional in parentheses to use
+    /// it as an operand.
+    fn parse_conditional(&mut self, start: Span) -> Result<Expression> {
+        let condition = self.parse_expression()?;
+        self.consume(Token::Toh, "Expected 'toh' after condition")?;
+        let then_branch = self.parse_expression()?;
+        self.consume(Token::Varna, "Expected 'varna' in conditional expression")?;
+        let else_branch = self.parse_expression()?;
+
+        Ok(Expression::Conditional {
+            span: start.to(else_branch.span()),
+            condition: Box::new(condition),
+            then_branch: Box::new(then_branch),
+            else_branch: Box::new(else_branch),
+        })
+    }
+
+    /// Parses the expressions embedded in an `f"..."` string
+    fn parse_interpolated_string(
+        &mut self,
+        format_parts: Vec<FormatPart>,
+        span: Span,
+    ) -> Result<Expression> {
+        let mut parts = Vec::new();
+
+        for part in format_parts {
+            match part {
+                FormatPart::Text(text) => parts.push(StringPart::Text(text)),
+                FormatPart::Code { source, offset } => {
+                    let mut parser = Parser::new(Lexer::with_offset(&source, offset)?);
+                    let expression = parser.parse_expression()?;
+                    if !parser.lexer.is_at_end() {
+                        return Err(
+                            parser.error_at_current("Expected '}' after interpolated expression")
+                        );
+                    }
+                    parts.push(StringPart::Expr(expression));
+                }
+            }
+        }
+
+        Ok(Expression::InterpolatedString { parts, span })
     }
 
     fn parse_type(&mut self) -> Result<Type> {
+        let base_type = self.parse_base_type()?;
+
+        // A trailing '?' makes the type optional: `int?`
+        if matches!(self.lexer.peek(), Some(Token::Question)) {
+            self.lexer.advance();
+            return Ok(Type::Optional(Box::new(base_type)));
+        }
+        Ok(base_type)
+    }
+
+    fn parse_base_type(&mut self) -> Result<Type> {
         match self.lexer.advance() {
             Some(Token::IntType) => Ok(Type::Int),
             Some(Token::FloatType) => Ok(Type::Float),
@@ -473,11 +1564,45 @@ impl Parser {
             Some(Token::ArrayType) => {
                 self.consume(Token::Less, "Expected '<' after 'array'")?;
                 let inner_type = self.parse_type()?;
-                self.consume(Token::Greater, "Expected '>' after array element type")?;
+                self.consume_closing_angle("Expected '>' after array element type")?;
                 Ok(Type::Array(Box::new(inner_type)))
             }
-            _ => Err(anyhow!("Expected type annotation")),
+            Some(Token::LeftParen) => {
+                let mut elements = vec![self.parse_type()?];
+                while matches!(self.lexer.peek(), Some(Token::Comma)) {
+                    self.lexer.advance();
+                    elements.push(self.parse_type()?);
+                }
+                self.consume(Token::RightParen, "Expected ')' after tuple element types")?;
+                if elements.len() < 2 {
+                    return Err(self.error_at_previous("A tuple type needs at least two elements"));
+                }
+                Ok(Type::Tuple(elements))
+            }
+            Some(Token::MapType) => {
+                self.consume(Token::Less, "Expected '<' after 'map'")?;
+                let key_type = self.parse_type()?;
+                self.consume(Token::Comma, "Expected ',' after map key type")?;
+                let value_type = self.parse_type()?;
+                self.consume_closing_angle("Expected '>' after map value type")?;
+                Ok(Type::Map(Box::new(key_type), Box::new(value_type)))
+            }
+            Some(Token::Identifier(name)) => Ok(Type::Named(name.clone())),
+            Some(Token::KuchNahi) => {
+                Err(self
+                    .error_at_previous("kuch_nahi can only be used as a function's return type"))
+            }
+            _ => Err(self.error_at_previous("Expected type annotation")),
+        }
+    }
+
+    /// Consumes a '>' closing a generic type, splitting a '>>' token if needed
+    fn consume_closing_angle(&mut self, message: &str) -> Result<()> {
+        if matches!(self.lexer.peek(), Some(Token::ShiftRight)) {
+            self.lexer.split_current(Token::Greater);
+            return Ok(());
         }
+        self.consume(Token::Greater, message)
     }
 
     fn consume(&mut self, expected: Token, message: &str) -> Result<()> {
@@ -486,7 +1611,118 @@ impl Parser {
                 self.lexer.advance();
                 Ok(())
             }
-            _ => Err(anyhow!("{}", message)),
+            // A missing ';' belongs to the end of the previous line, not the next token
+            _ if expected == Token::Semicolon => {
+                let end = self.lexer.previous_span().end;
+                Err(PaneerError::expected(Expected::Semicolon, message).at(Span::new(end, end)))
+            }
+            _ => {
+                let error = match expected {
+                    Token::RightBrace => PaneerError::expected(Expected::ClosingBrace, message),
+                    Token::RightParen => PaneerError::expected(Expected::ClosingParen, message),
+                    _ => PaneerError::syntax(message),
+                };
+                Err(error.at(self.lexer.peek_span()))
+            }
         }
     }
+
+    /// Returns a span from the start of `start` to the end of the last consumed token
+    fn span_from(&self, start: Span) -> Span {
+        start.to(self.lexer.previous_span())
+    }
+
+    /// Creates an error pointing at the token about to be consumed
+    fn error_at_current(&self, message: &str) -> PaneerError {
+        PaneerError::syntax(message).at(self.lexer.peek_span())
+    }
+
+    /// Creates an error pointing at the token that was just consumed
+    fn error_at_previous(&self, message: &str) -> PaneerError {
+        PaneerError::syntax(message).at(self.lexer.previous_span())
+    }
+
+    /// Creates an error for a missing construct, pointing at the token that was just consumed
+    fn expected_at_previous(&self, expected: Expected, message: &str) -> PaneerError {
+        PaneerError::expected(expected, message).at(self.lexer.previous_span())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Parses `source` and returns the syntax errors it reports
+    fn parse_errors(source: &str) -> Vec<PaneerError> {
+        Parser::new(Lexer::new(source).unwrap()).parse().1
+    }
+
+    #[test]
+    fn every_missing_semicolon_is_reported() {
+        let source = "ye n: int = 1;\npaneer.bol(n)\npaneer.bol(n)\npaneer.bol(n)\n";
+        assert_eq!(parse_errors(source).len(), 3);
+
+        let source = "ye x: int = 1;\nx = 2\nx = 3\nx = 4\n";
+        assert_eq!(parse_errors(source).len(), 3);
+    }
+
+    #[test]
+    fn recovery_stops_at_a_name_starting_a_line() {
+        let source = "ye x: int = ;\nx = 5 +;\nfoo(1 2);\n";
+        assert_eq!(parse_errors(source).len(), 3);
+    }
+
+    #[test]
+    fn negating_the_smallest_int_literal_overflows() {
+        for source in [
+            "paneer.bol(-(-9223372036854775808));",
+            "paneer.bol(- -9223372036854775808);",
+        ] {
+            let errors = parse_errors(source);
+            assert_eq!(errors.len(), 1, "{}", source);
+            assert_eq!(errors[0].code(), "E0203");
+        }
+        assert!(parse_errors("paneer.bol(-9223372036854775808);").is_empty());
+    }
+
+    #[test]
+    fn loop_control_errors_know_about_functions_inside_loops() {
+        let in_function_in_loop = |source| match &parse_errors(source)[..] {
+            [
+                PaneerError::LoopControl {
+                    in_function_in_loop,
+                    ..
+                },
+            ] => *in_function_in_loop,
+            errors => panic!("{}: {:?}", source, errors),
+        };
+        assert!(!in_function_in_loop("ruko;"));
+        assert!(!in_function_in_loop("func f() { aage badho; }"));
+        assert!(in_function_in_loop(
+            "jabtak true { func f() { ruko; } ruko; }"
+        ));
+        assert!(in_function_in_loop(
+            "jabtak true { func f() { func g() { aage badho; } } }"
+        ));
+    }
+
+    #[test]
+    fn doc_comments_belong_to_the_declaration_below_them() {
+        let source = "/// Ek point\n/// do numbers ka\ndhancha Point { x: int, y: int }\n\
+                      /// Lost\npaneer.bol(1);\nye n: int = 1;\n\
+                      /// Jodo\nfunc add(a int, b int) int { wapas kar a + b; }\n";
+        let (program, errors) = Parser::new(Lexer::new(source).unwrap()).parse();
+        assert!(errors.is_empty(), "{:?}", errors);
+        let docs: Vec<Option<&str>> = program
+            .statements
+            .iter()
+            .filter_map(|statement| match statement {
+                Statement::StructDecl { doc, .. }
+                | Statement::VarDecl { doc, .. }
+                | Statement::FuncDecl { doc, .. } => Some(doc.as_deref()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(docs, [Some("Ek point\ndo numbers ka"), None, Some("Jodo")]);
+    }
 }
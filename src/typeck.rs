@@ -0,0 +1,1848 @@
+//! Static type checker for PaneerLang
+//!
+//! Walks a parsed `Program` before it runs and reports every type error it can
+//! find, so a mistake in a branch that never executes is still caught.
+
+use crate::ast::*;
+use crate::errors::{NameKind, PaneerError, Warning};
+use std::collections::HashMap;
+
+/// Parameter and return types of a declared function
+#[derive(Debug, Clone)]
+struct FunctionSignature {
+    params: Vec<(String, Type)>,
+    return_type: Type,
+}
+
+/// Names declared in a single block
+///
+/// A variable maps to `None` when its type could not be determined because
+/// of an earlier error, which keeps one mistake from being reported twice.
+#[derive(Debug, Default, Clone)]
+struct Scope {
+    variables: HashMap<String, Option<Type>>,
+    functions: HashMap<String, FunctionSignature>,
+    structs: HashMap<String, Vec<(String, Type)>>,
+    enums: HashMap<String, Vec<(String, Vec<Type>)>>,
+}
+
+/// Checks programs for type errors without running them
+#[derive(Clone)]
+pub struct TypeChecker {
+    /// Stack of scopes, innermost last
+    scopes: Vec<Scope>,
+    /// Declared return type of the function whose body is being checked
+    return_type: Option<Type>,
+    /// Errors found so far
+    errors: Vec<PaneerError>,
+    /// Warnings found so far
+    warnings: Vec<Warning>,
+}
+
+impl TypeChecker {
+    /// Creates a type checker with an empty global scope
+    pub fn new() -> Self {
+        TypeChecker {
+            scopes: vec![Scope::default()],
+            return_type: None,
+            errors: Vec::new(),
+            warnings: Vec::new(),
+        }
+    }
+
+    /// Returns the warnings found since the last call, such as `jab`
+    /// statements that may not match every value
+    pub fn take_warnings(&mut self) -> Vec<Warning> {
+        std::mem::take(&mut self.warnings)
+    }
+
+    /// Checks a whole program and reports every error found
+    ///
+    /// Top-level declarations are remembered between calls, so REPL inputs
+    /// can refer to variables and functions declared earlier.
+    ///
+    /// # Returns
+    /// * `Ok(())` - If the program is well typed
+    /// * `Err(Vec<PaneerError>)` - All type errors, in source order
+    pub fn check(&mut self, program: &Program) -> Result<(), Vec<PaneerError>> {
+        self.check_block(&program.statements);
+
+        if self.errors.is_empty() {
+            Ok(())
+        } else {
+            Err(std::mem::take(&mut self.errors))
+        }
+    }
+
+    /// Checks statements in the current scope
+    ///
+    /// Function signatures, struct fields and enum variants are collected
+    /// first so that functions can call each other and use structs and enums
+    /// regardless of the order they are declared in. Function bodies are
+    /// checked where they are declared, so they only see the variables
+    /// declared before the function.
+    fn check_block(&mut self, statements: &[Statement]) {
+        for statement in statements {
+            match statement {
+                Statement::FuncDecl {
+                    name,
+                    params,
+                    return_type,
+                    ..
+                } => {
+                    self.current_scope().functions.insert(
+                        name.clone(),
+                        FunctionSignature {
+                            params: params.clone(),
+                            return_type: return_type.clone(),
+                        },
+                    );
+                }
+                Statement::StructDecl { name, fields, .. } => {
+                    self.current_scope()
+                        .structs
+                        .insert(name.clone(), fields.clone());
+                }
+                Statement::EnumDecl { name, variants, .. } => {
+                    self.current_scope()
+                        .enums
+                        .insert(name.clone(), variants.clone());
+                }
+                _ => {}
+            }
+        }
+
+        for statement in statements {
+            self.check_statement(statement);
+        }
+    }
+
+    /// Checks a block in a new scope of its own
+    fn check_scoped_block(&mut self, statements: &[Statement]) {
+        self.scopes.push(Scope::default());
+        self.check_block(statements);
+        self.scopes.pop();
+    }
+
+    fn check_statement(&mut self, statement: &Statement) {
+        match statement {
+            Statement::VarDecl {
+                name,
+                type_annotation,
+                initializer,
+                span,
+                ..
+            } => {
+                let known = self.check_type(type_annotation, *span);
+                if let Some(value_type) = self.check_value(initializer)
+                    && known
+                    && !fits(initializer, &value_type, type_annotation)
+                {
+                    self.error(
+                        PaneerError::type_error(format!(
+                            "Type mismatch: expected {}, got {}{}",
+                            type_annotation,
+                            value_type,
+                            unwrap_hint(&value_type, type_annotation)
+                        )),
+                        initializer.span(),
+                    );
+                }
+
+                self.current_scope()
+                    .variables
+                    .insert(name.clone(), Some(type_annotation.clone()));
+            }
+
+            Statement::TupleDecl {
+                names,
+                type_annotation,
+                initializer,
+                span,
+            } => {
+                let known = self.check_type(type_annotation, *span);
+                if let Some(value_type) = self.check_value(initializer)
+                    && known
+                    && !fits(initializer, &value_type, type_annotation)
+                {
+                    self.error(
+                        PaneerError::type_error(format!(
+                            "Type mismatch: expected {}, got {}",
+                            type_annotation, value_type
+                        )),
+                        initializer.span(),
+                    );
+                }
+
+                let element_types = match type_annotation {
+                    Type::Tuple(types) if types.len() == names.len() => {
+                        types.iter().cloned().map(Some).collect()
+                    }
+                    Type::Tuple(types) => {
+                        self.error(
+                            PaneerError::type_error(format!(
+                                "Cannot destructure {} into {} name(s), it has {} element(s)",
+                                type_annotation,
+                                names.len(),
+                                types.len()
+                            )),
+                            *span,
+                        );
+                        vec![None; names.len()]
+                    }
+                    other => {
+                        self.error(
+                            PaneerError::type_error(format!(
+                                "Can only destructure a tuple, got {}",
+                                other
+                            )),
+                            *span,
+                        );
+                        vec![None; names.len()]
+                    }
+                };
+
+                for (name, element_type) in names.iter().zip(element_types) {
+                    if name != "_" {
+                        self.current_scope()
+                            .variables
+                            .insert(name.clone(), element_type);
+                    }
+                }
+            }
+
+            Statement::FuncDecl {
+                name,
+                params,
+                return_type,
+                body,
+                span,
+                ..
+            } => {
+                for (_, param_type) in params {
+                    self.check_type(param_type, *span);
+                }
+                self.check_type(return_type, *span);
+
+                self.scopes.push(Scope::default());
+                for (param_name, param_type) in params {
+                    self.current_scope()
+                        .variables
+                        .insert(param_name.clone(), Some(param_type.clone()));
+                }
+
+                let enclosing = self.return_type.replace(return_type.clone());
+                self.check_block(body);
+                self.return_type = enclosing;
+                self.scopes.pop();
+
+                if *return_type != Type::Unit && !self.block_returns(body) {
+                    self.error(
+                        PaneerError::type_error(format!(
+                            "Function {} must return {} on every path",
+                            name, return_type
+                        )),
+                        *span,
+                    );
+                }
+            }
+
+            Statement::StructDecl { fields, span, .. } => {
+                for (_, field_type) in fields {
+                    self.check_type(field_type, *span);
+                }
+            }
+
+            Statement::EnumDecl { variants, span, .. } => {
+                for payload_type in variants.iter().flat_map(|(_, payload)| payload) {
+                    self.check_type(payload_type, *span);
+                }
+            }
+
+            Statement::ExprStmt { expression, .. } => {
+                self.check_expression(expression);
+            }
+
+            Statement::Assignment {
+                target,
+                operator,
+                value,
+                span,
+            } => {
+                let target_type = self.check_expression(target);
+                let value_type = self.check_value(value);
+
+                if let (Some(target_type), Some(value_type)) = (target_type, value_type) {
+                    let result_type = match operator {
+                        Some(operator) => {
+                            match binary_result_type(*operator, &target_type, &value_type) {
+                                Some(result_type) => result_type,
+                                None => {
+                                    self.error(
+                                        invalid_binary_operation(
+                                            *operator,
+                                            &target_type,
+                                            &value_type,
+                                        ),
+                                        *span,
+                                    );
+                                    return;
+                                }
+                            }
+                        }
+                        None => value_type,
+                    };
+
+                    if !fits(value, &result_type, &target_type) {
+                        self.error(
+                            PaneerError::type_error(format!(
+                                "Type mismatch: expected {}, got {}{}",
+                                target_type,
+                                result_type,
+                                unwrap_hint(&result_type, &target_type)
+                            )),
+                            value.span(),
+                        );
+                    }
+                }
+            }
+
+            Statement::IfStmt {
+                condition,
+                then_branch,
+                else_branch,
+                ..
+            } => {
+                self.check_expression(condition);
+                self.check_scoped_block(then_branch);
+                if let Some(else_branch) = else_branch {
+                    self.check_scoped_block(else_branch);
+                }
+            }
+
+            Statement::IfLetStmt {
+                pattern,
+                value,
+                then_branch,
+                else_branch,
+                ..
+            } => {
+                // The pattern is tested against what an optional holds
+                let value_type = self
+                    .check_expression(value)
+                    .map(|value_type| match value_type {
+                        Type::Optional(inner) => *inner,
+                        other => other,
+                    });
+
+                self.scopes.push(Scope::default());
+                self.check_pattern(pattern, value_type);
+                self.check_block(then_branch);
+                self.scopes.pop();
+
+                if let Some(else_branch) = else_branch {
+                    self.check_scoped_block(else_branch);
+                }
+            }
+
+            Statement::ReturnStmt { value, span } => {
+                let value_type = match value {
+                    Some(expression) => self.check_expression(expression),
+                    None => Some(Type::Unit),
+                };
+
+                match self.return_type.clone() {
+                    None => self.error(
+                        PaneerError::syntax("Return statement outside of function"),
+                        *span,
+                    ),
+                    Some(return_type) => {
+                        if let Some(value_type) = value_type
+                            && value_type != return_type
+                            && !value
+                                .as_ref()
+                                .is_some_and(|value| fits(value, &value_type, &return_type))
+                        {
+                            let value_span = value.as_ref().map_or(*span, |value| value.span());
+                            match tuple_element_mismatch(value.as_ref(), &value_type, &return_type)
+                            {
+                                // Point at the wrong element of a tuple literal
+                                Some((index, actual, expected)) => self.error(
+                                    PaneerError::type_error(format!(
+                                        "Return type mismatch in tuple element {}: expected {}, got {}{}",
+                                        index,
+                                        expected,
+                                        actual,
+                                        unwrap_hint(actual, expected)
+                                    )),
+                                    match value {
+                                        Some(Expression::TupleLiteral { elements, .. }) => {
+                                            elements[index].span()
+                                        }
+                                        _ => value_span,
+                                    },
+                                ),
+                                None => self.error(
+                                    PaneerError::type_error(format!(
+                                        "Return type mismatch: expected {}, got {}{}",
+                                        return_type,
+                                        value_type,
+                                        unwrap_hint(&value_type, &return_type)
+                                    )),
+                                    value_span,
+                                ),
+                            }
+                        }
+                    }
+                }
+            }
+
+            // The parser already rejects these outside of loops
+            Statement::BreakStmt { .. } | Statement::ContinueStmt { .. } => {}
+
+            Statement::WhileStmt {
+                condition, body, ..
+            } => {
+                self.check_expression(condition);
+                self.check_scoped_block(body);
+            }
+
+            Statement::ForStmt {
+                variable,
+                value_variable,
+                variable_type,
+                value_type,
+                iterable,
+                body,
+                ..
+            } => {
+                // Types of the first and second loop variables
+                let (first_type, second_type) = match self.check_expression(iterable) {
+                    Some(Type::Array(inner)) if value_variable.is_some() => {
+                        (Some(Type::Int), Some(*inner))
+                    }
+                    Some(Type::Array(inner)) => (Some(*inner), None),
+                    Some(Type::Map(key, value)) => (Some(*key), Some(*value)),
+                    Some(other) => {
+                        self.error(
+                            PaneerError::type_error(format!(
+                                "Can only iterate over arrays and maps, got {}",
+                                other
+                            )),
+                            iterable.span(),
+                        );
+                        (None, None)
+                    }
+                    None => (None, None),
+                };
+                if let Some(first_type) = &first_type {
+                    let _ = variable_type.set(first_type.clone());
+                }
+                if let Some(second_type) = &second_type {
+                    let _ = value_type.set(second_type.clone());
+                }
+
+                self.scopes.push(Scope::default());
+                self.current_scope()
+                    .variables
+                    .insert(variable.clone(), first_type);
+                if let Some(value_variable) = value_variable {
+                    self.current_scope()
+                        .variables
+                        .insert(value_variable.clone(), second_type);
+                }
+                self.check_block(body);
+                self.scopes.pop();
+            }
+
+            Statement::RangeForStmt {
+                variable,
+                start,
+                end,
+                step,
+                body,
+                ..
+            } => {
+                let bounds = [
+                    ("start", Some(start)),
+                    ("end", Some(end)),
+                    ("step", step.as_ref()),
+                ];
+                for (part, bound) in bounds {
+                    if let Some(bound) = bound
+                        && let Some(bound_type) = self.check_expression(bound)
+                        && bound_type != Type::Int
+                    {
+                        self.error(
+                            PaneerError::type_error(format!(
+                                "Type mismatch: range {} must be int, got {}",
+                                part, bound_type
+                            )),
+                            bound.span(),
+                        );
+                    }
+                }
+
+                self.scopes.push(Scope::default());
+                self.current_scope()
+                    .variables
+                    .insert(variable.clone(), Some(Type::Int));
+                self.check_block(body);
+                self.scopes.pop();
+            }
+
+            Statement::MatchStmt {
+                subject,
+                arms,
+                span,
+            } => {
+                let subject_type = self.check_expression(subject);
+
+                for (index, arm) in arms.iter().enumerate() {
+                    if self.match_is_exhaustive(&arms[..index]) {
+                        self.warnings
+                            .push(Warning::new("Unreachable match arm", arm.span));
+                    }
+
+                    self.scopes.push(Scope::default());
+                    self.check_pattern(&arm.pattern, subject_type.clone());
+                    if let Some(guard) = &arm.guard {
+                        self.check_expression(guard);
+                    }
+                    self.check_block(&arm.body);
+                    self.scopes.pop();
+                }
+
+                if !self.match_is_exhaustive(arms) {
+                    self.warnings.push(Warning::new(
+                        "Match may not cover every value; add a '_ => ...' arm",
+                        Span::new(span.start, subject.span().end),
+                    ));
+                }
+            }
+        }
+    }
+
+    /// Checks that `pattern` can match a value of `subject_type` and declares
+    /// the names it binds in the current scope
+    fn check_pattern(&mut self, pattern: &Pattern, subject_type: Option<Type>) {
+        let span = pattern.span();
+        match pattern {
+            Pattern::Wildcard { .. } => {}
+            Pattern::Binding {
+                name, binding_type, ..
+            } => {
+                if let Some(subject_type) = &subject_type {
+                    let _ = binding_type.set(subject_type.clone());
+                }
+                self.current_scope()
+                    .variables
+                    .insert(name.clone(), subject_type);
+            }
+            Pattern::Literal { value, .. } => {
+                // `khaali` matches the empty value of any optional, and plain
+                // values match an optional holding them
+                let fits = match (value, &subject_type) {
+                    (LiteralValue::Khaali, Some(Type::Optional(_))) => true,
+                    (_, Some(subject_type)) => type_fits(&value.get_type(), subject_type),
+                    (_, None) => true,
+                };
+                if !fits && let Some(subject_type) = subject_type {
+                    self.error(
+                        PaneerError::type_error(format!(
+                            "Pattern type mismatch: expected {}, got {}",
+                            subject_type,
+                            value.get_type()
+                        )),
+                        span,
+                    );
+                }
+            }
+            Pattern::Range { start, end, .. } => {
+                let bound_type = match (start, end) {
+                    (LiteralValue::Int(_), LiteralValue::Int(_))
+                    | (LiteralValue::Float(_), LiteralValue::Float(_)) => start.get_type(),
+                    _ => {
+                        self.error(
+                            PaneerError::type_error(format!(
+                                "Range pattern bounds must both be int or both be float, got {} and {}",
+                                start.get_type(),
+                                end.get_type()
+                            )),
+                            span,
+                        );
+                        return;
+                    }
+                };
+
+                if let Some(subject_type) = subject_type
+                    && bound_type != subject_type
+                {
+                    self.error(
+                        PaneerError::type_error(format!(
+                            "Pattern type mismatch: expected {}, got {}",
+                            subject_type, bound_type
+                        )),
+                        span,
+                    );
+                }
+            }
+            Pattern::Array {
+                elements,
+                rest,
+                rest_type: declared_rest_type,
+                ..
+            } => {
+                let element_type = match subject_type {
+                    Some(Type::Array(inner)) => Some(*inner),
+                    Some(other) => {
+                        self.error(
+                            PaneerError::type_error(format!(
+                                "Pattern type mismatch: expected {}, got an array pattern",
+                                other
+                            )),
+                            span,
+                        );
+                        None
+                    }
+                    None => None,
+                };
+
+                for element in elements {
+                    self.check_pattern(element, element_type.clone());
+                }
+                if let Some(Some(name)) = rest {
+                    let rest_type = element_type.map(|inner| Type::Array(Box::new(inner)));
+                    if let Some(rest_type) = &rest_type {
+                        let _ = declared_rest_type.set(rest_type.clone());
+                    }
+                    self.current_scope()
+                        .variables
+                        .insert(name.clone(), rest_type);
+                }
+            }
+            Pattern::Variant {
+                enum_name,
+                variant,
+                payload,
+                ..
+            } => {
+                let payload_types = self.variant_payload(enum_name, variant, span);
+
+                if let Some(subject_type) = &subject_type
+                    && *subject_type != Type::Named(enum_name.clone())
+                {
+                    self.error(
+                        PaneerError::type_error(format!(
+                            "Pattern type mismatch: expected {}, got {}",
+                            subject_type, enum_name
+                        )),
+                        span,
+                    );
+                }
+
+                match payload_types {
+                    Some(payload_types) if payload_types.len() != payload.len() => {
+                        self.error(
+                            PaneerError::type_error(format!(
+                                "Variant {}::{} expects {} value(s), got {}",
+                                enum_name,
+                                variant,
+                                payload_types.len(),
+                                payload.len()
+                            )),
+                            span,
+                        );
+                    }
+                    payload_types => {
+                        for (index, element) in payload.iter().enumerate() {
+                            let element_type = payload_types
+                                .as_ref()
+                                .map(|payload_types| payload_types[index].clone());
+                            self.check_pattern(element, element_type);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Works out the type of an expression, recording any errors inside it
+    ///
+    /// Returns `None` when the type cannot be determined because of an error.
+    fn check_expression(&mut self, expression: &Expression) -> Option<Type> {
+        match expression {
+            Expression::Literal { value, .. } => Some(value.get_type()),
+
+            Expression::Variable { name, span } => match self.lookup_variable(name) {
+                Some(var_type) => var_type,
+                None => {
+                    self.error(PaneerError::undefined(NameKind::Variable, name), *span);
+                    None
+                }
+            },
+
+            Expression::Binary {
+                left,
+                operator,
+                right,
+                span,
+            } => {
+                let left_type = self.check_value(left);
+                let right_type = self.check_value(right);
+
+                // Logical operators work on the truthiness of any value
+                if matches!(operator, BinaryOperator::And | BinaryOperator::Or) {
+                    return Some(Type::Bool);
+                }
+
+                if matches!(operator, BinaryOperator::Coalesce) {
+                    return self.check_coalesce(left, left_type?, right, right_type?);
+                }
+
+                let (left_type, right_type) = (left_type?, right_type?);
+                let result = binary_result_type(*operator, &left_type, &right_type);
+                if result.is_none() {
+                    self.error(
+                        invalid_binary_operation(*operator, &left_type, &right_type),
+                        *span,
+                    );
+                }
+                result
+            }
+
+            Expression::Unary {
+                operator,
+                operand,
+                span,
+            } => {
+                let operand_type = self.check_value(operand)?;
+                match (operator, operand_type) {
+                    (UnaryOperator::Minus, Type::Int) => Some(Type::Int),
+                    (UnaryOperator::Minus, Type::Float) => Some(Type::Float),
+                    (UnaryOperator::Not, _) => Some(Type::Bool),
+                    (UnaryOperator::Minus, other) => {
+                        self.error(
+                            PaneerError::type_error(format!(
+                                "Invalid unary operation: cannot negate {}",
+                                other
+                            )),
+                            *span,
+                        );
+                        None
+                    }
+                }
+            }
+
+            Expression::Call {
+                callee,
+                arguments,
+                span,
+            } => {
+                let argument_types: Vec<Option<Type>> = arguments
+                    .iter()
+                    .map(|argument| self.check_expression(argument))
+                    .collect();
+
+                let Expression::Variable { name, .. } = callee.as_ref() else {
+                    self.error(PaneerError::runtime("Invalid function call"), *span);
+                    return None;
+                };
+
+                let Some(signature) = self.lookup_function(name) else {
+                    self.error(PaneerError::undefined(NameKind::Function, name), *span);
+                    return None;
+                };
+
+                if arguments.len() != signature.params.len() {
+                    self.error(
+                        PaneerError::arity(name, signature.params.len(), arguments.len()),
+                        *span,
+                    );
+                } else {
+                    for ((argument, argument_type), (param_name, param_type)) in
+                        arguments.iter().zip(argument_types).zip(&signature.params)
+                    {
+                        if let Some(argument_type) = argument_type
+                            && !fits(argument, &argument_type, param_type)
+                        {
+                            self.error(
+                                PaneerError::type_error(format!(
+                                    "Argument type mismatch for parameter {}: expected {}, got {}{}",
+                                    param_name,
+                                    param_type,
+                                    argument_type,
+                                    unwrap_hint(&argument_type, param_type)
+                                )),
+                                argument.span(),
+                            );
+                        }
+                    }
+                }
+
+                Some(signature.return_type)
+            }
+
+            Expression::MethodCall {
+                object,
+                method,
+                arguments,
+                span,
+            } => {
+                let argument_types: Vec<Option<Type>> = arguments
+                    .iter()
+                    .map(|argument| self.check_expression(argument))
+                    .collect();
+
+                if let Expression::Variable { name, .. } = object.as_ref()
+                    && name == "paneer"
+                    && method == "bol"
+                {
+                    if arguments.len() != 1 {
+                        self.error(PaneerError::arity("paneer.bol", 1, arguments.len()), *span);
+                    }
+                    return Some(Type::Unit);
+                }
+
+                let object_name = match object.as_ref() {
+                    Expression::Variable { name, .. } => name.as_str(),
+                    _ => "unknown",
+                };
+                let object_type = self.check_expression(object)?;
+                let Type::Map(key_type, value_type) = object_type else {
+                    self.error(
+                        PaneerError::type_error(format!(
+                            "Unknown method: {}.{}",
+                            object_name, method
+                        )),
+                        *span,
+                    );
+                    return None;
+                };
+
+                let (params, result_type) = match method.as_str() {
+                    "has" | "remove" => (vec![*key_type], Type::Bool),
+                    "keys" => (vec![], Type::Array(key_type)),
+                    "values" => (vec![], Type::Array(value_type)),
+                    "len" => (vec![], Type::Int),
+                    _ => {
+                        self.error(
+                            PaneerError::type_error(format!(
+                                "Unknown method: {}.{} (maps have has, remove, keys, values and len)",
+                                object_name, method
+                            )),
+                            *span,
+                        );
+                        return None;
+                    }
+                };
+
+                if arguments.len() != params.len() {
+                    self.error(
+                        PaneerError::arity(method, params.len(), arguments.len()),
+                        *span,
+                    );
+                } else if let (Some(expected), Some(Some(argument_type))) =
+                    (params.first(), argument_types.first())
+                    && argument_type != expected
+                {
+                    self.error(
+                        PaneerError::type_error(format!(
+                            "Map key type mismatch: expected {}, got {}",
+                            expected, argument_type
+                        )),
+                        arguments[0].span(),
+                    );
+                }
+
+                Some(result_type)
+            }
+
+            Expression::ArrayLiteral { elements, .. } => {
+                let elements: Vec<&Expression> = elements.iter().collect();
+                let element_type = self.common_type(&elements, |expected, current| {
+                    format!(
+                        "Array elements must all have the same type: expected {}, got {}",
+                        expected, current
+                    )
+                });
+
+                match element_type {
+                    Some(element_type) => Some(Type::Array(Box::new(element_type))),
+                    // Empty arrays default to array<int>, as they do at runtime
+                    None if elements.is_empty() => Some(Type::Array(Box::new(Type::Int))),
+                    None => None,
+                }
+            }
+
+            Expression::Conditional {
+                condition,
+                then_branch,
+                else_branch,
+                ..
+            } => {
+                self.check_expression(condition);
+                self.common_type(&[then_branch, else_branch], |then_type, else_type| {
+                    format!(
+                        "Conditional branches have different types: {} and {}",
+                        then_type, else_type
+                    )
+                })
+            }
+
+            Expression::InterpolatedString { parts, .. } => {
+                for part in parts {
+                    if let StringPart::Expr(expression) = part {
+                        self.check_expression(expression);
+                    }
+                }
+                Some(Type::String)
+            }
+
+            Expression::ArrayAccess { array, index, .. } => {
+                let array_type = self.check_expression(array);
+                let index_type = self.check_expression(index);
+
+                let (expected_index, element_type) = match array_type? {
+                    Type::Array(inner) => (Type::Int, *inner),
+                    Type::Map(key, value) => (*key, *value),
+                    other => {
+                        self.error(
+                            PaneerError::type_error(format!(
+                                "Invalid array access: {} is not an array or map",
+                                other
+                            )),
+                            array.span(),
+                        );
+                        return None;
+                    }
+                };
+
+                if let Some(index_type) = index_type
+                    && index_type != expected_index
+                {
+                    let message = if expected_index == Type::Int {
+                        format!(
+                            "Invalid array access: index must be int, got {}",
+                            index_type
+                        )
+                    } else {
+                        format!(
+                            "Map key type mismatch: expected {}, got {}",
+                            expected_index, index_type
+                        )
+                    };
+                    self.error(PaneerError::type_error(message), index.span());
+                }
+
+                Some(element_type)
+            }
+
+            Expression::MapLiteral { entries, span } => {
+                let keys: Vec<&Expression> = entries.iter().map(|(key, _)| key).collect();
+                let values: Vec<&Expression> = entries.iter().map(|(_, value)| value).collect();
+                let [key_type, value_type] =
+                    [(keys, "keys"), (values, "values")].map(|(expressions, part)| {
+                        self.common_type(&expressions, |expected, current| {
+                            format!(
+                                "Map {} must all have the same type: expected {}, got {}",
+                                part, expected, current
+                            )
+                        })
+                    });
+
+                match (key_type, value_type) {
+                    (Some(key_type), Some(value_type)) => {
+                        let map_type = Type::Map(Box::new(key_type), Box::new(value_type));
+                        self.check_type(&map_type, *span).then_some(map_type)
+                    }
+                    // Empty maps default to map<int, int>, as they do at runtime
+                    _ if entries.is_empty() => {
+                        Some(Type::Map(Box::new(Type::Int), Box::new(Type::Int)))
+                    }
+                    _ => None,
+                }
+            }
+
+            Expression::StructLiteral { name, fields, span } => {
+                let declared = self.lookup_struct(name);
+                let field_types: Vec<Option<Type>> = fields
+                    .iter()
+                    .map(|(_, value)| self.check_expression(value))
+                    .collect();
+
+                let Some(declared) = declared else {
+                    self.error(
+                        PaneerError::type_error(format!("Unknown type: {}", name)),
+                        *span,
+                    );
+                    return None;
+                };
+
+                for (index, ((field, value), value_type)) in
+                    fields.iter().zip(field_types).enumerate()
+                {
+                    let Some((_, expected)) = declared.iter().find(|(f, _)| f == field) else {
+                        self.error(
+                            PaneerError::type_error(format!("{} has no field {}", name, field)),
+                            value.span(),
+                        );
+                        continue;
+                    };
+                    if fields[..index].iter().any(|(f, _)| f == field) {
+                        self.error(
+                            PaneerError::type_error(format!(
+                                "Field {} is given more than once",
+                                field
+                            )),
+                            value.span(),
+                        );
+                    } else if let Some(value_type) = value_type
+                        && !fits(value, &value_type, expected)
+                    {
+                        self.error(
+                            PaneerError::type_error(format!(
+                                "Type mismatch for field {}: expected {}, got {}{}",
+                                field,
+                                expected,
+                                value_type,
+                                unwrap_hint(&value_type, expected)
+                            )),
+                            value.span(),
+                        );
+                    }
+                }
+
+                for (field, _) in &declared {
+                    if !fields.iter().any(|(f, _)| f == field) {
+                        self.error(
+                            PaneerError::type_error(format!("Missing field {} in {}", field, name)),
+                            *span,
+                        );
+                    }
+                }
+
+                Some(Type::Named(name.clone()))
+            }
+
+            Expression::EnumVariant {
+                enum_name,
+                variant,
+                arguments,
+                span,
+            } => {
+                let argument_types: Vec<Option<Type>> = arguments
+                    .iter()
+                    .map(|argument| self.check_expression(argument))
+                    .collect();
+                let payload_types = self.variant_payload(enum_name, variant, *span)?;
+
+                if arguments.len() != payload_types.len() {
+                    self.error(
+                        PaneerError::type_error(format!(
+                            "Variant {}::{} expects {} value(s), got {}",
+                            enum_name,
+                            variant,
+                            payload_types.len(),
+                            arguments.len()
+                        )),
+                        *span,
+                    );
+                } else {
+                    for ((argument, argument_type), expected) in
+                        arguments.iter().zip(argument_types).zip(&payload_types)
+                    {
+                        if let Some(argument_type) = argument_type
+                            && !fits(argument, &argument_type, expected)
+                        {
+                            self.error(
+                                PaneerError::type_error(format!(
+                                    "Type mismatch in {}::{} payload: expected {}, got {}{}",
+                                    enum_name,
+                                    variant,
+                                    expected,
+                                    argument_type,
+                                    unwrap_hint(&argument_type, expected)
+                                )),
+                                argument.span(),
+                            );
+                        }
+                    }
+                }
+
+                Some(Type::Named(enum_name.clone()))
+            }
+
+            Expression::TupleLiteral { elements, .. } => {
+                let mut element_types = Vec::new();
+                for element in elements {
+                    element_types.push(self.check_expression(element));
+                }
+                element_types
+                    .into_iter()
+                    .collect::<Option<Vec<_>>>()
+                    .map(Type::Tuple)
+            }
+
+            Expression::TupleAccess { tuple, index, span } => {
+                match self.check_expression(tuple)? {
+                    Type::Tuple(types) => {
+                        let element_type = types.get(*index).cloned();
+                        if element_type.is_none() {
+                            self.error(
+                                PaneerError::type_error(format!(
+                                    "Tuple index {} out of range for {}",
+                                    index,
+                                    Type::Tuple(types)
+                                )),
+                                *span,
+                            );
+                        }
+                        element_type
+                    }
+                    other => {
+                        self.error(
+                            PaneerError::type_error(format!(
+                                "Cannot read element {} of {}, it is not a tuple",
+                                index, other
+                            )),
+                            *span,
+                        );
+                        None
+                    }
+                }
+            }
+
+            Expression::FieldAccess {
+                object,
+                field,
+                span,
+            } => match self.check_expression(object)? {
+                Type::Named(name) => {
+                    let field_type = self.lookup_struct(&name).and_then(|fields| {
+                        fields
+                            .into_iter()
+                            .find(|(f, _)| f == field)
+                            .map(|(_, field_type)| field_type)
+                    });
+                    if field_type.is_none() {
+                        self.error(
+                            PaneerError::type_error(format!("{} has no field {}", name, field)),
+                            *span,
+                        );
+                    }
+                    field_type
+                }
+                other => {
+                    self.error(
+                        PaneerError::type_error(format!(
+                            "Cannot access field {} on {}",
+                            field, other
+                        )),
+                        *span,
+                    );
+                    None
+                }
+            },
+        }
+    }
+
+    fn current_scope(&mut self) -> &mut Scope {
+        self.scopes
+            .last_mut()
+            .expect("type checker always has a global scope")
+    }
+
+    /// Finds a variable in the innermost scope that declares it
+    fn lookup_variable(&self, name: &str) -> Option<Option<Type>> {
+        self.scopes
+            .iter()
+            .rev()
+            .find_map(|scope| scope.variables.get(name).cloned())
+    }
+
+    /// Finds a function in the innermost scope that declares it
+    fn lookup_function(&self, name: &str) -> Option<FunctionSignature> {
+        self.scopes
+            .iter()
+            .rev()
+            .find_map(|scope| scope.functions.get(name).cloned())
+    }
+
+    /// Finds a struct in the innermost scope that declares it
+    fn lookup_struct(&self, name: &str) -> Option<Vec<(String, Type)>> {
+        self.scopes
+            .iter()
+            .rev()
+            .find_map(|scope| scope.structs.get(name).cloned())
+    }
+
+    /// Finds an enum in the innermost scope that declares it
+    fn lookup_enum(&self, name: &str) -> Option<Vec<(String, Vec<Type>)>> {
+        self.scopes
+            .iter()
+            .rev()
+            .find_map(|scope| scope.enums.get(name).cloned())
+    }
+
+    /// Looks up the payload types of `Enum::Variant`, reporting an unknown
+    /// enum or variant at `span`
+    fn variant_payload(&mut self, enum_name: &str, variant: &str, span: Span) -> Option<Vec<Type>> {
+        let Some(variants) = self.lookup_enum(enum_name) else {
+            self.error(
+                PaneerError::type_error(format!("Unknown type: {}", enum_name)),
+                span,
+            );
+            return None;
+        };
+
+        let payload = variants
+            .into_iter()
+            .find(|(name, _)| name == variant)
+            .map(|(_, payload)| payload);
+        if payload.is_none() {
+            self.error(
+                PaneerError::type_error(format!("{} has no variant {}", enum_name, variant)),
+                span,
+            );
+        }
+        payload
+    }
+
+    /// Checks an expression whose result is used as a value, rejecting the
+    /// result of a function that returns `kuch_nahi`
+    fn check_value(&mut self, expression: &Expression) -> Option<Type> {
+        match self.check_expression(expression)? {
+            Type::Unit => {
+                self.error(
+                    PaneerError::type_error("This returns kuch_nahi, so there is no value to use"),
+                    expression.span(),
+                );
+                None
+            }
+            value_type => Some(value_type),
+        }
+    }
+
+    /// Finds the one type shared by values that must agree, such as the
+    /// elements of an array literal, reporting each value that does not fit
+    ///
+    /// A plain type and its optional combine into the optional, and `khaali`
+    /// makes the shared type optional.
+    fn common_type(
+        &mut self,
+        values: &[&Expression],
+        mismatch: impl Fn(&Type, &Type) -> String,
+    ) -> Option<Type> {
+        let mut common: Option<Type> = None;
+        let mut saw_khaali = false;
+        let mut unknown = false;
+        for expression in values {
+            if is_khaali(expression) {
+                saw_khaali = true;
+                continue;
+            }
+            let Some(current) = self.check_expression(expression) else {
+                unknown = true;
+                continue;
+            };
+            common = match common {
+                None => Some(current),
+                Some(expected) if type_fits(&current, &expected) => Some(expected),
+                Some(expected) if type_fits(&expected, &current) => Some(current),
+                Some(expected) => {
+                    self.error(
+                        PaneerError::type_error(mismatch(&expected, &current)),
+                        expression.span(),
+                    );
+                    Some(expected)
+                }
+            };
+        }
+
+        match common {
+            Some(Type::Optional(inner)) => Some(Type::Optional(inner)),
+            Some(common) if saw_khaali => Some(Type::Optional(Box::new(common))),
+            None if unknown => None,
+            // `khaali` alone defaults to int?, as it does at runtime
+            None if saw_khaali => Some(Type::Optional(Box::new(Type::Int))),
+            common => common,
+        }
+    }
+
+    /// Checks `left ?? right`, which yields the plain type when the default
+    /// is plain and stays optional when the default is itself optional
+    fn check_coalesce(
+        &mut self,
+        left: &Expression,
+        left_type: Type,
+        right: &Expression,
+        right_type: Type,
+    ) -> Option<Type> {
+        let Type::Optional(inner) = left_type else {
+            self.error(
+                PaneerError::type_error(format!(
+                    "'??' needs an optional on the left, got {}",
+                    left_type
+                )),
+                left.span(),
+            );
+            return None;
+        };
+
+        if fits(right, &right_type, &inner) {
+            Some(*inner)
+        } else if fits(right, &right_type, &Type::Optional(inner.clone())) {
+            Some(Type::Optional(inner))
+        } else {
+            self.error(
+                PaneerError::type_error(format!(
+                    "Default for '??' must be {}, got {}",
+                    inner, right_type
+                )),
+                right.span(),
+            );
+            None
+        }
+    }
+
+    /// Reports struct and enum names in `ty` that were never declared, and
+    /// map key types that cannot be compared, returning whether `ty` is valid
+    fn check_type(&mut self, ty: &Type, span: Span) -> bool {
+        match ty {
+            Type::Array(inner) => self.check_type(inner, span),
+            Type::Optional(inner) => self.check_type(inner, span),
+            Type::Tuple(elements) => {
+                // Check every element so each unknown type is reported
+                let mut known = true;
+                for element in elements {
+                    known &= self.check_type(element, span);
+                }
+                known
+            }
+            Type::Map(key, value) => {
+                if !matches!(**key, Type::Int | Type::String | Type::Bool) {
+                    self.error(
+                        PaneerError::type_error(format!(
+                            "Map keys must be int, string or bool, got {}",
+                            key
+                        )),
+                        span,
+                    );
+                    return false;
+                }
+                self.check_type(value, span)
+            }
+            Type::Named(name)
+                if self.lookup_struct(name).is_none() && self.lookup_enum(name).is_none() =>
+            {
+                self.error(
+                    PaneerError::type_error(format!("Unknown type: {}", name)),
+                    span,
+                );
+                false
+            }
+            _ => true,
+        }
+    }
+
+    /// Whether every path through `statements` ends in a return
+    fn block_returns(&self, statements: &[Statement]) -> bool {
+        statements.iter().any(|statement| match statement {
+            Statement::ReturnStmt { .. } => true,
+            Statement::IfStmt {
+                then_branch,
+                else_branch: Some(else_branch),
+                ..
+            }
+            | Statement::IfLetStmt {
+                then_branch,
+                else_branch: Some(else_branch),
+                ..
+            } => self.block_returns(then_branch) && self.block_returns(else_branch),
+            Statement::MatchStmt { arms, .. } => {
+                self.match_is_exhaustive(arms)
+                    && arms.iter().all(|arm| self.block_returns(&arm.body))
+            }
+            _ => false,
+        })
+    }
+
+    /// Whether some arm is certain to run, either because its pattern matches
+    /// every value or because the arms cover both bools, every array length or
+    /// every variant of an enum, with no guards involved
+    fn match_is_exhaustive(&self, arms: &[MatchArm]) -> bool {
+        let unguarded = arms.iter().filter(|arm| arm.guard.is_none());
+        if unguarded.clone().any(|arm| arm.pattern.is_irrefutable()) {
+            return true;
+        }
+
+        let mut enum_name = None;
+        let mut covered = Vec::new();
+        let mut bools = Vec::new();
+        let mut exact_lengths = Vec::new();
+        let mut shortest_rest = None;
+        for arm in unguarded {
+            match &arm.pattern {
+                Pattern::Variant {
+                    enum_name: name,
+                    variant,
+                    payload,
+                    ..
+                } if payload.iter().all(Pattern::is_irrefutable) => {
+                    enum_name = Some(name);
+                    covered.push(variant);
+                }
+                Pattern::Literal {
+                    value: LiteralValue::Bool(value),
+                    ..
+                } => bools.push(*value),
+                Pattern::Array { elements, rest, .. }
+                    if elements.iter().all(Pattern::is_irrefutable) =>
+                {
+                    match rest {
+                        Some(_) => {
+                            shortest_rest =
+                                Some(shortest_rest.map_or(elements.len(), |shortest: usize| {
+                                    shortest.min(elements.len())
+                                }))
+                        }
+                        None => exact_lengths.push(elements.len()),
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        // `[]` and `[x, ..rest]` cover every length, as does any set of exact
+        // lengths below the shortest rest pattern
+        if bools.contains(&true) && bools.contains(&false)
+            || shortest_rest
+                .is_some_and(|shortest| (0..shortest).all(|len| exact_lengths.contains(&len)))
+        {
+            return true;
+        }
+
+        enum_name
+            .and_then(|name| self.lookup_enum(name))
+            .is_some_and(|variants| {
+                variants
+                    .iter()
+                    .all(|(variant, _)| covered.contains(&variant))
+            })
+    }
+
+    fn error(&mut self, error: PaneerError, span: Span) {
+        self.errors.push(error.at(span));
+    }
+}
+
+/// Whether `expression`, whose type is `actual`, can be stored where
+/// `expected` is required
+///
+/// An optional type also accepts `khaali` and plain values of its inner type;
+/// the reverse needs an explicit unwrap.
+fn fits(expression: &Expression, actual: &Type, expected: &Type) -> bool {
+    if type_fits(actual, expected) || is_empty_collection(expression, expected) {
+        return true;
+    }
+    match expected {
+        Type::Optional(inner) => is_khaali(expression) || fits(expression, actual, inner),
+        _ => false,
+    }
+}
+
+/// Whether every value of type `actual` is also a value of type `expected`
+///
+/// This holds for equal types and for a plain type stored as its optional,
+/// including inside arrays, map values and tuples.
+fn type_fits(actual: &Type, expected: &Type) -> bool {
+    match (actual, expected) {
+        _ if actual == expected => true,
+        (Type::Array(actual), Type::Array(expected)) => type_fits(actual, expected),
+        (Type::Map(actual_key, actual_value), Type::Map(expected_key, expected_value)) => {
+            actual_key == expected_key && type_fits(actual_value, expected_value)
+        }
+        (Type::Tuple(actual), Type::Tuple(expected)) => {
+            actual.len() == expected.len()
+                && actual
+                    .iter()
+                    .zip(expected)
+                    .all(|(actual, expected)| type_fits(actual, expected))
+        }
+        (Type::Optional(actual), Type::Optional(expected)) => type_fits(actual, expected),
+        (actual, Type::Optional(expected)) => type_fits(actual, expected),
+        _ => false,
+    }
+}
+
+/// Finds the first element of a tuple of type `actual` that cannot be stored
+/// where a tuple of type `expected` is required, returning its index and types
+///
+/// `expression` is the tuple's value, so elements of a tuple literal get the
+/// same leniency towards `khaali` and empty collections as other values.
+fn tuple_element_mismatch<'a>(
+    expression: Option<&Expression>,
+    actual: &'a Type,
+    expected: &'a Type,
+) -> Option<(usize, &'a Type, &'a Type)> {
+    let (Type::Tuple(actual), Type::Tuple(expected)) = (actual, expected) else {
+        return None;
+    };
+    if actual.len() != expected.len() {
+        return None;
+    }
+    actual
+        .iter()
+        .zip(expected)
+        .enumerate()
+        .find(|(index, (actual, expected))| match expression {
+            Some(Expression::TupleLiteral { elements, .. }) => {
+                !fits(&elements[*index], actual, expected)
+            }
+            _ => !type_fits(actual, expected),
+        })
+        .map(|(index, (actual, expected))| (index, actual, expected))
+}
+
+/// Whether `expression` is the `khaali` literal
+fn is_khaali(expression: &Expression) -> bool {
+    matches!(
+        expression,
+        Expression::Literal {
+            value: LiteralValue::Khaali,
+            ..
+        }
+    )
+}
+
+/// Suggests unwrapping when an optional is used where its plain type is needed
+fn unwrap_hint(actual: &Type, expected: &Type) -> &'static str {
+    match actual {
+        Type::Optional(inner) if **inner == *expected => {
+            " (it may be khaali; unwrap it with ?? or agar ye first)"
+        }
+        _ => "",
+    }
+}
+
+/// Whether `expression` is an empty `[]` or `{}` literal stored where a
+/// collection of the same kind is expected
+///
+/// An empty literal has no element types of its own, so it fits any array or
+/// map type.
+fn is_empty_collection(expression: &Expression, expected: &Type) -> bool {
+    match (expression, expected) {
+        (Expression::ArrayLiteral { elements, .. }, Type::Array(_)) => elements.is_empty(),
+        (Expression::MapLiteral { entries, .. }, Type::Map(..)) => entries.is_empty(),
+        _ => false,
+    }
+}
+
+/// Returns the type a binary operator produces for the given operand types,
+/// or `None` if the interpreter would reject the combination
+fn binary_result_type(operator: BinaryOperator, left: &Type, right: &Type) -> Option<Type> {
+    use BinaryOperator::*;
+
+    match (operator, left, right) {
+        // String concatenation converts the other operand automatically
+        (Add, Type::String, _) | (Add, _, Type::String) => Some(Type::String),
+        (Add | Subtract | Multiply | Divide | Modulo | Power, Type::Int, Type::Int) => {
+            Some(Type::Int)
+        }
+        (Add | Subtract | Multiply | Divide | Modulo | Power, Type::Float, Type::Float) => {
+            Some(Type::Float)
+        }
+        (BitAnd | BitOr | BitXor | ShiftLeft | ShiftRight, Type::Int, Type::Int) => Some(Type::Int),
+        (Equal | NotEqual | And | Or, _, _) => Some(Type::Bool),
+        (Greater | Less | GreaterEqual | LessEqual, Type::Int, Type::Int)
+        | (Greater | Less | GreaterEqual | LessEqual, Type::Float, Type::Float) => Some(Type::Bool),
+        _ => None,
+    }
+}
+
+fn invalid_binary_operation(operator: BinaryOperator, left: &Type, right: &Type) -> PaneerError {
+    let hint = if matches!(left, Type::Optional(_)) || matches!(right, Type::Optional(_)) {
+        " (an optional may be khaali; unwrap it with ?? or agar ye first)"
+    } else {
+        ""
+    };
+    PaneerError::type_error(format!(
+        "Invalid binary operation: {} {:?} {}{}",
+        left, operator, right, hint
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lexer::Lexer;
+    use crate::parser::Parser;
+
+    /// Parses and type checks `source`, returning the messages of any errors
+    fn check(source: &str) -> Vec<String> {
+        let (program, parse_errors) = Parser::new(Lexer::new(source).unwrap()).parse();
+        assert!(parse_errors.is_empty(), "{:?}", parse_errors);
+        match TypeChecker::new().check(&program) {
+            Ok(()) => Vec::new(),
+            Err(errors) => errors.iter().map(|error| error.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn function_bodies_only_see_variables_declared_before_them() {
+        assert_eq!(
+            check("func f() int { return g; } paneer.bol(f()); ye g: int = 1;"),
+            ["Undefined variable: g"]
+        );
+        assert_eq!(
+            check("ye g: int = 1; func f() int { return g + h(); } func h() int { return g; }"),
+            Vec::<String>::new()
+        );
+    }
+
+    #[test]
+    fn errors_are_reported_in_source_order() {
+        let errors = check(r#"func f() int { return "s"; } ye x: int = true;"#);
+        assert_eq!(
+            errors,
+            [
+                "Return type mismatch: expected int, got string",
+                "Type mismatch: expected int, got bool"
+            ]
+        );
+    }
+
+    #[test]
+    fn khaali_and_plain_values_combine_into_an_optional() {
+        let source = r#"
+            ye a: array<int?> = [1, khaali];
+            ye b: array<int?> = [khaali, 1];
+            ye m: map<string, int?> = {"a": 1, "b": khaali};
+            ye c: bool = true;
+            ye x: int? = agar c toh 1 varna khaali;
+            ye y: string? = agar c toh khaali varna "s";
+            ye z: array<int?> = [1, 2];
+        "#;
+        assert_eq!(check(source), Vec::<String>::new());
+    }
+
+    #[test]
+    fn optionals_are_not_plain_values() {
+        assert_eq!(
+            check("ye a: array<int> = [1, khaali];"),
+            ["Type mismatch: expected array<int>, got array<int?>"]
+        );
+        assert_eq!(check("ye a: int? = 1; ye b: int = a + 1;").len(), 1);
+    }
+
+    #[test]
+    fn bool_and_array_matches_can_be_exhaustive() {
+        let source = r#"
+            func g(b bool) int {
+                jab b {
+                    true => wapas kar 1;
+                    false => wapas kar 0;
+                }
+            }
+            func total(items array<int>) int {
+                jab items {
+                    [] => wapas kar 0;
+                    [first, ..rest] => wapas kar first + total(rest);
+                }
+            }
+            func pairs(items array<int>) int {
+                jab items {
+                    [] => wapas kar 0;
+                    [a] => wapas kar a;
+                    [a, b, ..] => wapas kar a + b;
+                }
+            }
+        "#;
+        let (program, parse_errors) = Parser::new(Lexer::new(source).unwrap()).parse();
+        assert!(parse_errors.is_empty(), "{:?}", parse_errors);
+        let mut checker = TypeChecker::new();
+        assert!(checker.check(&program).is_ok());
+        assert!(checker.take_warnings().is_empty());
+
+        assert_eq!(
+            check("func h(items array<int>) int { jab items { [] => wapas kar 0; [a, b, ..] => wapas kar a; } }")
+                .len(),
+            1
+        );
+    }
+
+    #[test]
+    fn unknown_methods_are_type_errors() {
+        for source in [
+            r#"ye m: map<string, int> = {"a": 1}; m.push(2);"#,
+            "ye n: int = 1; n.len();",
+        ] {
+            let (program, _) = Parser::new(Lexer::new(source).unwrap()).parse();
+            let errors = TypeChecker::new().check(&program).unwrap_err();
+            assert!(
+                matches!(errors[..], [PaneerError::Type { .. }]),
+                "{}: {:?}",
+                source,
+                errors
+            );
+        }
+    }
+
+    #[test]
+    fn tuple_return_mismatches_name_the_element() {
+        assert_eq!(
+            check(r#"func f() (int, string) { wapas kar (1, 2); }"#),
+            ["Return type mismatch in tuple element 1: expected string, got int"]
+        );
+        assert_eq!(
+            check(r#"func g() (int?, string) { wapas kar (khaali, "a"); }"#),
+            Vec::<String>::new()
+        );
+        assert_eq!(
+            check("func h() (int, int) { wapas kar (1, 2, 3); }"),
+            ["Return type mismatch: expected (int, int), got (int, int, int)"]
+        );
+    }
+
+    #[test]
+    fn khaali_patterns_match_any_optional() {
+        assert_eq!(
+            check(
+                r#"
+                ye s: string? = khaali;
+                jab s {
+                    khaali => paneer.bol("none");
+                    "chai" => paneer.bol("chai");
+                    _ => paneer.bol("other");
+                }
+                "#
+            ),
+            Vec::<String>::new()
+        );
+        assert_eq!(
+            check("ye n: int = 1; jab n { khaali => paneer.bol(n); _ => paneer.bol(n); }"),
+            ["Pattern type mismatch: expected int, got int?"]
+        );
+    }
+
+    #[test]
+    fn conditional_expressions_take_the_type_of_their_branches() {
+        assert_eq!(
+            check(
+                r#"
+                ye n: int = 7;
+                ye kind: string = agar n % 2 == 0 toh "even" varna "odd";
+                ye sign: int = agar n > 0 toh 1 varna agar n < 0 toh -1 varna 0;
+                ye total: int = 100 + (agar n > 5 toh 0 varna 50);
+                ye maybe: int? = agar n > 5 toh n varna khaali;
+                "#
+            ),
+            Vec::<String>::new()
+        );
+        assert_eq!(
+            check(r#"ye x: int = agar true toh 1 varna "one";"#),
+            ["Conditional branches have different types: int and string"]
+        );
+        assert_eq!(
+            check("ye x: string = agar true toh 1 varna 2;"),
+            ["Type mismatch: expected string, got int"]
+        );
+        assert_eq!(
+            check("ye x: int = agar true toh 1 varna khaali;"),
+            [
+                "Type mismatch: expected int, got int? (it may be khaali; unwrap it with ?? or agar ye first)"
+            ]
+        );
+    }
+
+    #[test]
+    fn struct_literals_give_every_field_once_with_its_type() {
+        let point = "dhancha Point { x: int, y: int }";
+        assert_eq!(
+            check(&format!("{} ye p: Point = Point {{ x: 1 }};", point)),
+            ["Missing field y in Point"]
+        );
+        assert_eq!(
+            check(&format!(
+                "{} ye p: Point = Point {{ x: 1, y: 2, z: 3 }};",
+                point
+            )),
+            ["Point has no field z"]
+        );
+        assert_eq!(
+            check(&format!(
+                "{} ye p: Point = Point {{ x: 1, x: 2, y: 3 }};",
+                point
+            )),
+            ["Field x is given more than once"]
+        );
+        assert_eq!(
+            check(&format!(
+                r#"{} ye p: Point = Point {{ x: 1, y: "2" }};"#,
+                point
+            )),
+            ["Type mismatch for field y: expected int, got string"]
+        );
+        assert_eq!(
+            check(&format!(
+                "{} ye p: Point = Point {{ x: 1, y: 2 }}; p.z = 3;",
+                point
+            )),
+            ["Point has no field z"]
+        );
+    }
+
+    #[test]
+    fn variant_patterns_are_checked_against_the_enum() {
+        let status = "prakar Status { Pending, Paid(float) } ye s: Status = Status::Paid(1.5);";
+        assert_eq!(
+            check(&format!(
+                "{} jab s {{ Status::Paid(a, b) => paneer.bol(1); _ => paneer.bol(0); }}",
+                status
+            )),
+            ["Variant Status::Paid expects 1 value(s), got 2"]
+        );
+        assert_eq!(
+            check(&format!(
+                "{} agar ye Status::Refunded = s {{ paneer.bol(1); }}",
+                status
+            )),
+            ["Status has no variant Refunded"]
+        );
+        assert_eq!(
+            check(&format!(
+                "{} agar ye Status::Paid(1) = s {{ paneer.bol(1); }}",
+                status
+            )),
+            ["Pattern type mismatch: expected float, got int"]
+        );
+        assert_eq!(
+            check(&format!(
+                "{} agar ye Status::Paid(a) = s {{ ye b: float = a * 2.0; }}",
+                status
+            )),
+            Vec::<String>::new()
+        );
+    }
+
+    #[test]
+    fn tuple_destructuring_and_access_follow_the_tuple_type() {
+        assert_eq!(
+            check("ye (a, b, c): (int, int) = (1, 2);"),
+            ["Cannot destructure (int, int) into 3 name(s), it has 2 element(s)"]
+        );
+        assert_eq!(
+            check("ye (a, b): int = 1;"),
+            ["Can only destructure a tuple, got int"]
+        );
+        assert_eq!(
+            check("ye t: (int, (int, bool)) = (1, (2, true)); ye b: bool = t.1.2;"),
+            ["Tuple index 2 out of range for (int, bool)"]
+        );
+        assert_eq!(
+            check(r#"ye t: (int, (string, bool)) = (1, ("a", true)); ye s: string = t.1.0;"#),
+            Vec::<String>::new()
+        );
+    }
+}
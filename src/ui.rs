@@ -47,7 +47,7 @@ pub fn print_repl_banner() {
     println!(
         "{} {}",
         "💡 Commands:".green().bold(),
-        "'exit' to quit, 'help' for help".yellow()
+        "'exit' to quit, 'help' for help, ':doc name' for docs".yellow()
     );
     println!(
         "{} {}",
@@ -146,20 +146,50 @@ pub fn print_help() {
         "  📌 {} - Variable declaration",
         "ye name: string = \"value\";".cyan()
     );
+    println!("  ✏️ {} - Assignment", "name = \"new value\";".cyan());
     println!(
         "  🖨 {} - Print statement",
         "paneer.bol(\"Hello World\");".cyan()
     );
     println!(
         "  ❓ {} - If statement",
-        "agar condition { ... } varna { ... }".cyan()
+        "agar cond { ... } varna agar cond { ... } varna { ... }".cyan()
     );
     println!(
         "  ⚙️ {} - Function declaration",
         "func add(a int, b int) int { return a + b; }".cyan()
     );
+    println!(
+        "  📣 {} - Function without a result (kuch_nahi)",
+        "func greet(name string) { paneer.bol(name); }".cyan()
+    );
+    println!(
+        "  🧱 {} - Struct declaration",
+        "dhancha Point { x: int, y: int }".cyan()
+    );
+    println!(
+        "  🏷 {} - Enum declaration",
+        "prakar Status { Pending, Paid(float) }".cyan()
+    );
+    println!(
+        "  🎯 {} - Pattern test",
+        "agar ye Status::Paid(amount) = s { ... }".cyan()
+    );
+    println!(
+        "  🔀 {} - Conditional expression",
+        "agar cond toh a varna b".cyan()
+    );
+    println!(
+        "  🧩 {} - Pattern matching",
+        "jab x { 1 => ...; 2 tak 5 => ...; _ => ...; }".cyan()
+    );
     println!("  🔁 {} - While loop", "jabtak condition { ... }".cyan());
     println!("  🔄 {} - For loop", "har item mein array { ... }".cyan());
+    println!(
+        "  🗺 {} - Map loop",
+        "har key, value mein map { ... }".cyan()
+    );
+    println!("  🔢 {} - Range loop", "har i se 1 tak 10 { ... }".cyan());
     println!(
         "  📊 {} - Array declaration",
         "ye arr: array<int> = [1, 2, 3];".cyan()
@@ -171,23 +201,49 @@ pub fn print_help() {
     println!("  📝 {} - Text strings", "string".cyan());
     println!("  ✅ {} - Boolean values (true/false)", "bool".cyan());
     println!("  📊 {} - Arrays of elements", "array<type>".cyan());
+    println!(
+        "  🗺 {} - Maps from keys to values",
+        "map<key, value>".cyan()
+    );
+    println!(
+        "  📦 {} - Tuples, read with t.0 or ye (a, b): (int, string) = t;",
+        "(type, type)".cyan()
+    );
+    println!(
+        "  ❔ {} - Optional: a value or khaali, unwrapped with ?? or agar ye",
+        "type?".cyan()
+    );
+    println!(
+        "  🧱 {} - Structs and enums declared with dhancha and prakar",
+        "Name".cyan()
+    );
     println!();
     println!("{}", "⚙️ Operators:".yellow().bold());
-    println!("  🧮 {} - Arithmetic", "+ - * /".cyan());
+    println!("  🧮 {} - Arithmetic", "+ - * / % **".cyan());
+    println!("  🔣 {} - Bitwise", "& | ^ << >>".cyan());
+    println!("  ✏️ {} - Assignment", "= += -= *= /= ++ --".cyan());
     println!("  ⚖️ {} - Comparison", "== != > < >= <=".cyan());
-    println!("  🧠 {} - Logical", "!".cyan());
+    println!("  🧠 {} - Logical", "! && || aur ya".cyan());
+    println!("  🫙 {} - Default for an optional", "??".cyan());
     println!();
     println!("{}", "🔑 Keywords (Hindi-inspired):".yellow().bold());
     println!("  📌 {} - Variable declaration (let/var)", "ye".cyan());
     println!("  ❓ {} - If statement (if)", "agar".cyan());
     println!("  ❌ {} - Else statement (else)", "varna".cyan());
+    println!("  🔀 {} - Else-if (else if)", "varna agar".cyan());
     println!("  ⚙️ {} - Function declaration (function)", "func".cyan());
+    println!("  🧱 {} - Struct declaration (struct)", "dhancha".cyan());
+    println!("  🏷 {} - Enum declaration (enum)", "prakar".cyan());
+    println!("  🫙 {} - Missing optional value (null)", "khaali".cyan());
     println!(
         "  ↩️ {} - Return statement (return)",
         "return / wapas kar".cyan()
     );
     println!("  🔁 {} - While loop (while)", "jabtak".cyan());
+    println!("  🧩 {} - Pattern matching (match)", "jab".cyan());
     println!("  🔄 {} - For loop (for...in)", "har...mein".cyan());
+    println!("  🛑 {} - Leave loop (break)", "ruko".cyan());
+    println!("  ⏭️ {} - Next iteration (continue)", "aage badho".cyan());
     println!("  🖨 {} - Print function (print)", "paneer.bol()".cyan());
     println!();
     println!(
@@ -199,6 +255,34 @@ pub fn print_help() {
     println!();
 }
 
+/// Signature and doc comment of a declaration made in the REPL
+pub struct DocEntry {
+    pub signature: String,
+    pub doc: Option<String>,
+}
+
+/// Prints the documentation shown by the REPL `:doc` command
+pub fn print_doc(name: &str, entry: Option<&DocEntry>) {
+    let Some(entry) = entry else {
+        println!(
+            "{} {}",
+            "❓ Nothing declared with the name".yellow(),
+            name.cyan()
+        );
+        return;
+    };
+
+    println!("{}", entry.signature.cyan().bold());
+    match &entry.doc {
+        Some(doc) => {
+            for line in doc.lines() {
+                println!("  {}", line);
+            }
+        }
+        None => println!("  {}", "(no doc comment)".bright_black()),
+    }
+}
+
 pub fn print_debug_info(source: &str) {
     println!("{}", "🐛 Debug Information:".red().bold());
     println!("{}", "─".repeat(40).bright_black());
@@ -1,4 +1,41 @@
 use crate::utils::colors::PaneerColors;
+use std::iter::Peekable;
+use std::str::Chars;
+
+/// Devanagari spellings of the keywords, as accepted by the lexer
+const DEVANAGARI_KEYWORDS: &[&str] = &[
+    "ये",
+    "अगर",
+    "तो",
+    "वरना",
+    "फंक",
+    "ढांचा",
+    "प्रकार",
+    "रिटर्न",
+    "वापस",
+    "कर",
+    "जबतक",
+    "जब",
+    "हर",
+    "में",
+    "से",
+    "तक",
+    "कदम",
+    "रुको",
+    "आगे",
+    "बढ़ो",
+    "ब\u{095D}ो",
+    "और",
+    "या",
+    "पनीर",
+    "बोल",
+    "सच",
+    "झूठ",
+    "खाली",
+];
+
+/// Devanagari spellings of the type names
+const DEVANAGARI_TYPES: &[&str] = &["इंट", "फ्लोट", "स्ट्रिंग", "बूल", "ऐरे", "मैप", "कुछ_नहीं"];
 
 pub struct SyntaxHighlighter {
     keywords: Vec<&'static str>,
@@ -9,10 +46,25 @@ impl SyntaxHighlighter {
     pub fn new() -> Self {
         SyntaxHighlighter {
             keywords: vec![
-                "ye", "agar", "varna", "func", "return", "wapas", "kar", "jabtak", "har", "mein",
-                "se", "tak", "paneer", "bol", "true", "false",
-            ],
-            types: vec!["int", "float", "string", "bool", "array"],
+                "ye", "agar", "toh", "varna", "func", "dhancha", "prakar", "return", "wapas",
+                "kar", "jabtak", "har", "mein", "se", "tak", "kadam", "ruko", "aage", "badho",
+                "jab", "aur", "ya", "paneer", "bol", "true", "false", "khaali",
+            ]
+            .into_iter()
+            .chain(DEVANAGARI_KEYWORDS.iter().copied())
+            .collect(),
+            types: vec![
+                "int",
+                "float",
+                "string",
+                "bool",
+                "array",
+                "map",
+                "kuch_nahi",
+            ]
+            .into_iter()
+            .chain(DEVANAGARI_TYPES.iter().copied())
+            .collect(),
         }
     }
 
@@ -32,36 +84,111 @@ impl SyntaxHighlighter {
                     }
                     let mut comment = String::from("//");
                     chars.next(); // consume second '/'
+                    let mut ended_line = false;
                     for ch in chars.by_ref() {
                         if ch == '\n' {
+                            ended_line = true;
                             break;
                         }
                         comment.push(ch);
                     }
                     result.push_str(&format!("{}", PaneerColors::comment(&comment)));
+                    if ended_line {
+                        result.push('\n');
+                    }
                 }
-                '"' => {
-                    // Handle strings
+                '/' if chars.peek() == Some(&'*') => {
+                    // Block comments, which may nest and span several lines
                     if !current_token.is_empty() {
                         result.push_str(&self.highlight_token(&current_token));
                         current_token.clear();
                     }
-                    let mut string_literal = String::from("\"");
+                    let mut comment = String::from("/*");
+                    chars.next(); // consume '*'
+                    let mut depth = 1;
+                    while depth > 0
+                        && let Some(ch) = chars.next()
+                    {
+                        comment.push(ch);
+                        if comment[2..].ends_with("/*") {
+                            depth += 1;
+                        } else if comment[2..].ends_with("*/") {
+                            depth -= 1;
+                        }
+                    }
+                    let coloured: Vec<String> = comment
+                        .split('\n')
+                        .map(|line| PaneerColors::comment(line).to_string())
+                        .collect();
+                    result.push_str(&coloured.join("\n"));
+                }
+                '"' => {
+                    // Handle strings; a lone `r` just before the quote makes it
+                    // raw and a lone `f` makes it an interpolated string
+                    let raw = current_token == "r";
+                    let prefix = if raw || current_token == "f" {
+                        current_token.clone()
+                    } else {
+                        String::new()
+                    };
+                    if !prefix.is_empty() {
+                        current_token.clear();
+                    } else if !current_token.is_empty() {
+                        result.push_str(&self.highlight_token(&current_token));
+                        current_token.clear();
+                    }
+
+                    let mut lookahead = chars.clone();
+                    let text_block = prefix.is_empty()
+                        && lookahead.next() == Some('"')
+                        && lookahead.next() == Some('"');
+
+                    let mut string_literal = format!("{}\"", prefix);
+                    if text_block {
+                        string_literal.push(chars.next().unwrap());
+                        string_literal.push(chars.next().unwrap());
+                    }
+
+                    // Inside `{}` of an interpolated string, quotes belong to
+                    // the embedded expression rather than ending the string
+                    let interpolated = prefix == "f";
+                    let mut depth = 0usize;
+                    let mut in_nested_string = false;
+
                     while let Some(ch) = chars.next() {
                         string_literal.push(ch);
-                        if ch == '"' {
+                        if interpolated {
+                            match ch {
+                                '"' if depth > 0 => in_nested_string = !in_nested_string,
+                                '{' | '}' if depth == 0 && chars.peek() == Some(&ch) => {
+                                    string_literal.push(chars.next().unwrap());
+                                    continue;
+                                }
+                                '{' if !in_nested_string => depth += 1,
+                                '}' if !in_nested_string => depth = depth.saturating_sub(1),
+                                _ => {}
+                            }
+                        }
+                        if ch == '"'
+                            && depth == 0
+                            && (!text_block || string_literal.ends_with("\"\"\""))
+                        {
                             break;
                         }
                         if ch == '\\'
+                            && !raw
                             && let Some(escaped) = chars.next()
                         {
                             string_literal.push(escaped);
                         }
                     }
-                    result.push_str(&format!(
-                        "{}",
-                        PaneerColors::string_literal(&string_literal)
-                    ));
+                    // Colour each line separately so multi-line strings survive
+                    // being printed line by line
+                    let coloured: Vec<String> = string_literal
+                        .split('\n')
+                        .map(|line| PaneerColors::string_literal(line).to_string())
+                        .collect();
+                    result.push_str(&coloured.join("\n"));
                 }
                 ' ' | '\t' | '\n' | '\r' => {
                     // Whitespace - process current token and add whitespace
@@ -79,7 +206,7 @@ impl SyntaxHighlighter {
                     }
                     result.push_str(&format!("{}", PaneerColors::punctuation(&ch.to_string())));
                 }
-                '+' | '-' | '*' | '/' | '=' | '!' | '<' | '>' => {
+                '+' | '-' | '*' | '/' | '%' | '=' | '!' | '<' | '>' | '&' | '|' | '^' => {
                     // Operators - check for multi-character operators
                     if !current_token.is_empty() {
                         result.push_str(&self.highlight_token(&current_token));
@@ -87,13 +214,20 @@ impl SyntaxHighlighter {
                     }
                     let mut operator = ch.to_string();
                     if let Some(&next_ch) = chars.peek()
-                        && (ch == '>' || ch == '<' || ch == '!' || ch == '=')
-                        && next_ch == '='
+                        && (next_ch == '='
+                            || (matches!(ch, '+' | '-' | '*' | '&' | '|' | '<' | '>')
+                                && next_ch == ch))
                     {
                         operator.push(chars.next().unwrap());
                     }
                     result.push_str(&format!("{}", PaneerColors::operator(&operator)));
                 }
+                '0'..='9' if current_token.is_empty() => {
+                    // Numbers, read whole so `0xFF`, `1_000` and `6.02e-23`
+                    // are coloured as one literal
+                    let number = Self::scan_number(ch, &mut chars);
+                    result.push_str(&format!("{}", PaneerColors::number_literal(&number)));
+                }
                 _ => {
                     // Regular character - add to current token
                     current_token.push(ch);
@@ -109,6 +243,38 @@ impl SyntaxHighlighter {
         result
     }
 
+    /// Reads the rest of a numeric literal starting with `first`
+    fn scan_number(first: char, chars: &mut Peekable<Chars>) -> String {
+        let mut number = first.to_string();
+        let prefixed =
+            first == '0' && matches!(chars.peek(), Some('x' | 'X' | 'b' | 'B' | 'o' | 'O'));
+
+        // True when the character after the next one is a digit
+        let digit_after_next = |chars: &Peekable<Chars>| {
+            let mut rest = chars.clone();
+            rest.next();
+            rest.next().is_some_and(|c| c.is_ascii_digit())
+        };
+
+        while let Some(&ch) = chars.peek() {
+            let exponent_sign = !prefixed
+                && matches!(ch, '+' | '-')
+                && number.ends_with(['e', 'E'])
+                && digit_after_next(chars);
+            let decimal_point =
+                !prefixed && ch == '.' && !number.contains('.') && digit_after_next(chars);
+
+            if ch.is_ascii_alphanumeric() || ch == '_' || exponent_sign || decimal_point {
+                number.push(ch);
+                chars.next();
+            } else {
+                break;
+            }
+        }
+
+        number
+    }
+
     fn highlight_token(&self, token: &str) -> String {
         // Check if it's a number
         if token.chars().all(|c| c.is_ascii_digit() || c == '.')
@@ -119,9 +285,9 @@ impl SyntaxHighlighter {
 
         // Check if it's a keyword
         if self.keywords.contains(&token) {
-            if token == "paneer" || token == "bol" {
+            if matches!(token, "paneer" | "bol" | "पनीर" | "बोल") {
                 return format!("{}", PaneerColors::special_keyword(token));
-            } else if token == "true" || token == "false" {
+            } else if matches!(token, "true" | "false" | "सच" | "झूठ") {
                 return format!("{}", PaneerColors::boolean(token));
             } else {
                 return format!("{}", PaneerColors::keyword(token));
@@ -148,8 +314,9 @@ pub fn print_code_block(title: &str, code: &str) {
         PaneerColors::border("┐")
     );
 
-    for line in code.lines() {
-        let highlighted = highlighter.highlight(line);
+    // Highlight the whole program at once so strings spanning lines are recognised
+    let highlighted_code = highlighter.highlight(code);
+    for highlighted in highlighted_code.lines() {
         println!(
             "{} {} {}",
             PaneerColors::border("│"),